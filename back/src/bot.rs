@@ -0,0 +1,34 @@
+use engine::{Action, PlayerId, Prompt};
+use rand::seq::SliceRandom;
+use shared::PromptKind;
+
+/// Picks an answer for a seat played by the server. Bots never reveal
+/// themselves nor use abilities, answer Hermit cards truthfully, and
+/// otherwise act at random while avoiding to hurt themselves.
+pub(crate) fn choose(player: PlayerId, prompt: &Prompt, choices: &[Action]) -> usize {
+    let skip = choices.iter().position(|&c| c == Action::Skip);
+    match prompt.kind {
+        PromptKind::RevealWindow | PromptKind::Ability => skip.unwrap_or(0),
+        // The engine proposes the truthful answer first
+        PromptKind::HermitAnswer => 0,
+        _ => {
+            if let Some(heal) = choices
+                .iter()
+                .position(|&c| matches!(c, Action::HealPlayer(p, _) if p == player))
+            {
+                return heal;
+            }
+            let candidates = (0..choices.len())
+                .filter(|&i| {
+                    !matches!(choices[i], Action::Skip | Action::HealPlayer(..))
+                        && !matches!(choices[i], Action::DamagePlayer(p, _) if p == player)
+                })
+                .collect::<Vec<_>>();
+            candidates
+                .choose(&mut rand::thread_rng())
+                .copied()
+                .or(skip)
+                .unwrap_or(0)
+        }
+    }
+}
@@ -1,80 +1,544 @@
-use std::{net::SocketAddr, sync::Arc};
+mod bot;
+mod rooms;
 
-use engine::PlayerId;
+use std::{
+    collections::VecDeque,
+    net::SocketAddr,
+    sync::Arc,
+    time::{Duration, Instant},
+};
+
+use engine::{PlayerId, RequestId};
 use futures::{
     stream::{SplitSink, SplitStream},
     SinkExt, StreamExt,
 };
-use tokio::sync::{mpsc, oneshot, Mutex};
+use shared::wire::{Encoding, Frame};
+use tokio::{
+    sync::{mpsc, oneshot, Mutex},
+    task::JoinHandle,
+};
 
 use axum::{
-    extract::ws::{self, WebSocket, WebSocketUpgrade},
+    extract::{
+        ws::{self, WebSocket, WebSocketUpgrade},
+        Query,
+    },
     http::StatusCode,
     response::{IntoResponse, Response},
-    routing::get,
+    routing::{get, post},
     Router,
 };
 
+struct PendingRequest {
+    id: RequestId,
+    prompt: engine::Prompt,
+    choices: Vec<engine::Action>,
+    response: oneshot::Sender<usize>,
+}
+
+impl PendingRequest {
+    fn message(&self) -> PlayerMessage {
+        PlayerMessage::ActionRequest {
+            id: self.id,
+            prompt: self.prompt.clone(),
+            choices: self.choices.clone(),
+        }
+    }
+}
+
 struct Player {
+    /// Identifies the connection of the player, its id being its position
+    /// in the room which changes when players leave the lobby.
+    key: usize,
     id: PlayerId,
-    tx: mpsc::Sender<PlayerMessage>,
-    request_answer: Option<oneshot::Sender<usize>>,
+    name: String,
+    color: Option<String>,
+    tx: mpsc::UnboundedSender<PlayerMessage>,
+    pending_requests: Vec<PendingRequest>,
+    /// Dead players stay connected as spectators of the rest of the game.
+    dead: bool,
+    connected: bool,
+    ready: bool,
+    /// Lets the player take its seat back after losing its connection.
+    token: String,
+    /// Seat played by the server, either added as such by the host or given
+    /// up by a player disconnected for too long.
+    bot: bool,
+    /// When the recent chat messages of the player were sent.
+    chat_times: VecDeque<Instant>,
 }
 
 impl Player {
-    fn new(id: PlayerId, tx: mpsc::Sender<PlayerMessage>) -> Self {
+    fn new(
+        key: usize,
+        id: PlayerId,
+        name: String,
+        color: Option<String>,
+        token: String,
+        tx: mpsc::UnboundedSender<PlayerMessage>,
+    ) -> Self {
         Self {
+            key,
             id,
+            name,
+            color,
             tx,
-            request_answer: None,
+            pending_requests: vec![],
+            dead: false,
+            connected: true,
+            ready: false,
+            token,
+            bot: false,
+            chat_times: VecDeque::new(),
         }
     }
+
+    /// Seat played by the server from the start, which nobody can reclaim.
+    fn new_bot(key: usize, id: PlayerId, name: String) -> Self {
+        let (tx, _) = mpsc::unbounded_channel();
+        Self {
+            connected: false,
+            ready: true,
+            bot: true,
+            ..Self::new(key, id, name, None, rooms::random_token(), tx)
+        }
+    }
+
+    /// Sends a message to the player, dropping it if the player is not
+    /// connected anymore. Reconnecting players are resynchronized.
+    ///
+    /// Messages are queued without waiting, so that a slow connection never
+    /// holds up the room.
+    fn send(&self, message: PlayerMessage) {
+        if !self.connected {
+            return;
+        }
+        if self.tx.send(message).is_err() {
+            println!("{:?} is disconnected, dropping message", self.id);
+        }
+    }
+
+    /// Whether the player may send another chat message, recording it if
+    /// so.
+    fn may_chat(&mut self) -> bool {
+        let now = Instant::now();
+        while self
+            .chat_times
+            .front()
+            .is_some_and(|&t| now.duration_since(t) > CHAT_WINDOW)
+        {
+            self.chat_times.pop_front();
+        }
+        if self.chat_times.len() >= CHAT_MESSAGES_PER_WINDOW {
+            return false;
+        }
+        self.chat_times.push_back(now);
+        true
+    }
+
+    /// Forwards a valid answer to the engine and returns whether it was
+    /// accepted. Answers to unknown requests are rejected, and out of range
+    /// choices are rejected before prompting the player again.
+    fn answer(&mut self, request: RequestId, choice: usize) -> bool {
+        let i = match self.pending_requests.iter().position(|r| r.id == request) {
+            Some(i) => i,
+            None => {
+                let error = shared::ErrorMessage::UnknownRequest(request);
+                self.send(PlayerMessage::Error(error));
+                return false;
+            }
+        };
+        if choice >= self.pending_requests[i].choices.len() {
+            let error = shared::ErrorMessage::InvalidChoice { request, choice };
+            let message = self.pending_requests[i].message();
+            self.send(PlayerMessage::Error(error));
+            self.send(message);
+            return false;
+        }
+        let pending = self.pending_requests.remove(i);
+        if pending.response.send(choice).is_err() {
+            println!("Engine stopped waiting for {:?}", request);
+        }
+        true
+    }
+}
+
+/// Connection following the game without taking part in it.
+struct Spectator {
+    key: usize,
+    /// Messages are stamped with the time they were emitted so that they
+    /// can be delayed.
+    tx: mpsc::UnboundedSender<(Instant, PlayerMessage)>,
+    /// Omniscient spectators see every character and private message, with
+    /// a delay so that streams cannot be used to help players.
+    omniscient: bool,
 }
 
+impl Spectator {
+    fn send(&self, message: PlayerMessage) {
+        if self.tx.send((Instant::now(), message)).is_err() {
+            println!("Spectator {} is disconnected, dropping message", self.key);
+        }
+    }
+}
+
+/// How often connections are pinged, writing to dead sockets eventually
+/// failing.
+const HEARTBEAT_INTERVAL: Duration = Duration::from_secs(30);
+/// Delay after which a connection whose writes are stuck is considered dead.
+const SEND_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Delay before omniscient spectators receive the messages of the game.
+const OMNISCIENT_DELAY: Duration = Duration::from_secs(30);
+
+/// Delay after which a room nobody is connected to is closed.
+const ABANDON_DELAY: Duration = Duration::from_secs(5 * 60);
+/// Delay a room stays open once its game is over, for players and
+/// spectators to see the end of the game and chat about it.
+const FINISHED_DELAY: Duration = Duration::from_secs(5 * 60);
+/// Delay a disconnected player has to come back before a bot takes its
+/// seat, the game waiting for it in the meantime. The player can still
+/// reclaim its seat afterwards.
+const RECONNECT_TIMEOUT: Duration = Duration::from_secs(2 * 60);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, serde::Serialize)]
 enum RoomState {
     Registration,
     Running,
+    Finished,
 }
 
 struct Room {
     state: RoomState,
+    /// Last time a player or spectator was connected, bots counting as
+    /// absent.
+    last_connected: Instant,
+    /// When the game ended.
+    finished: Option<Instant>,
+    /// Game and relay tasks, aborted when the room is closed.
+    tasks: Vec<JoinHandle<()>>,
     players: Vec<Player>,
+    spectators: Vec<Spectator>,
+    /// Information and chat messages of the game, replayed to reconnecting
+    /// players and new spectators.
+    log: Vec<LogEntry>,
+    /// Mirror of the engine state, kept up to date with every mutation so
+    /// that players can be resynchronized at any time.
+    game_state: Option<engine::state::State>,
+    /// House rule letting dead players see every character.
+    omniscient_dead: bool,
+    next_key: usize,
+    /// Secret given to the creator of the room, allowing to start the game.
+    host_token: String,
 }
 
 impl Room {
-    fn new() -> Self {
+    fn new(omniscient_dead: bool, host_token: String) -> Self {
         Self {
             state: RoomState::Registration,
+            last_connected: Instant::now(),
+            finished: None,
+            tasks: vec![],
             players: vec![],
+            spectators: vec![],
+            log: vec![],
+            game_state: None,
+            omniscient_dead,
+            next_key: 0,
+            host_token,
+        }
+    }
+
+    /// Whether the room can be closed: its game has been over for a while,
+    /// or nobody has been connected to it for a while.
+    fn is_over(&self) -> bool {
+        let connected = self.players.iter().any(|p| p.connected) || !self.spectators.is_empty();
+        self.finished.is_some_and(|f| f.elapsed() > FINISHED_DELAY)
+            || (!connected && self.last_connected.elapsed() > ABANDON_DELAY)
+    }
+
+    fn finish(&mut self) {
+        self.state = RoomState::Finished;
+        self.finished = Some(Instant::now());
+    }
+
+    /// Stops the game and closes the connections of the room.
+    fn close(&mut self) {
+        for task in self.tasks.drain(..) {
+            task.abort();
+        }
+        for p in &self.players {
+            p.send(PlayerMessage::Close);
         }
+        for s in &self.spectators {
+            s.send(PlayerMessage::Close);
+        }
+    }
+
+    fn remove_spectator(&mut self, key: usize) {
+        self.spectators.retain(|s| s.key != key);
+        self.last_connected = Instant::now();
+    }
+}
+
+/// Longest accepted player name, in characters.
+const MAX_NAME_LENGTH: usize = 32;
+/// Longest accepted chat message, in characters.
+const MAX_CHAT_LENGTH: usize = 500;
+/// Players may send that many chat messages within `CHAT_WINDOW`.
+const CHAT_MESSAGES_PER_WINDOW: usize = 5;
+const CHAT_WINDOW: Duration = Duration::from_secs(10);
+
+#[derive(Debug, serde::Deserialize)]
+struct JoinParams {
+    name: String,
+    color: Option<String>,
+}
+
+#[derive(Debug, serde::Deserialize)]
+struct HostParams {
+    /// Token given to the host when creating the room.
+    token: String,
+}
+
+#[derive(Debug, serde::Deserialize)]
+struct ReconnectParams {
+    token: String,
+}
+
+#[derive(Debug, serde::Deserialize)]
+struct SpectateParams {
+    #[serde(default)]
+    omniscient: bool,
+    /// Token given to the host when creating the room, needed to watch the
+    /// hidden information before the game is over.
+    token: Option<String>,
+}
+
+#[derive(Debug, serde::Deserialize)]
+struct StartParams {
+    /// Token given to the host when creating the room.
+    token: String,
+    packs: Option<String>,
+}
+
+/// Message of the game log.
+struct LogEntry {
+    /// Players who received the message, everyone if `None`.
+    audience: Option<Vec<PlayerId>>,
+    message: PlayerMessage,
+}
+
+impl LogEntry {
+    fn is_for(&self, id: PlayerId) -> bool {
+        self.audience.as_ref().is_none_or(|a| a.contains(&id))
     }
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 enum PlayerMessage {
-    ActionRequest { choices: Vec<engine::Action> },
-    Info { payload: engine::InfoMessage },
+    ActionRequest {
+        id: RequestId,
+        prompt: engine::Prompt,
+        choices: Vec<engine::Action>,
+    },
+    Info {
+        payload: engine::InfoMessage,
+    },
     StateMutation(engine::state::Mutation),
+    StateSync(Box<engine::state::State>),
+    WaitingFor(Vec<PlayerId>),
+    Error(shared::ErrorMessage),
+    Roster {
+        players: Vec<shared::PlayerProfile>,
+        you: PlayerId,
+    },
+    SessionToken(String),
+    Chat {
+        from: PlayerId,
+        channel: shared::ChatChannel,
+        text: String,
+    },
     Pong(Vec<u8>),
+    /// Closes the connection, the room being closed.
+    Close,
+}
+
+impl PlayerMessage {
+    /// Frame sent to the client, or `None` if the message could not be
+    /// serialized.
+    fn into_frame(self, encoding: Encoding) -> Option<ws::Message> {
+        let message = match self {
+            PlayerMessage::ActionRequest {
+                id,
+                prompt,
+                choices,
+            } => shared::ToPlayer::ActionRequest {
+                id,
+                prompt,
+                choices,
+            },
+            PlayerMessage::Info { payload } => shared::ToPlayer::Info(payload),
+            PlayerMessage::StateMutation(mutation) => shared::ToPlayer::StateMutation(mutation),
+            PlayerMessage::StateSync(state) => shared::ToPlayer::StateSync(state),
+            PlayerMessage::WaitingFor(players) => shared::ToPlayer::WaitingFor(players),
+            PlayerMessage::Error(error) => shared::ToPlayer::Error(error),
+            PlayerMessage::Roster { players, you } => shared::ToPlayer::Roster { players, you },
+            PlayerMessage::SessionToken(token) => shared::ToPlayer::SessionToken(token),
+            PlayerMessage::Chat {
+                from,
+                channel,
+                text,
+            } => shared::ToPlayer::Chat {
+                from,
+                channel,
+                text,
+            },
+            PlayerMessage::Pong(data) => return Some(ws::Message::Pong(data)),
+            PlayerMessage::Close => return Some(ws::Message::Close(None)),
+        };
+        to_frame(&message, encoding)
+    }
+}
+
+fn to_frame(message: &shared::ToPlayer, encoding: Encoding) -> Option<ws::Message> {
+    match Frame::encode(message, encoding) {
+        Ok(Frame::Text(text)) => Some(ws::Message::Text(text)),
+        Ok(Frame::Binary(bytes)) => Some(ws::Message::Binary(bytes)),
+        Err(e) => {
+            println!("Dropping {:?}: {:?}", message, e);
+            None
+        }
+    }
+}
+
+/// Delay clients have to say hello once connected.
+const HANDSHAKE_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Waits for the client to introduce itself and welcomes it with the
+/// features of the server, returning the encoding it asked for.
+/// Incompatible clients are told why before being disconnected.
+///
+/// The handshake itself is always in JSON.
+async fn handshake(socket: &mut WebSocket) -> Option<Encoding> {
+    let error = match tokio::time::timeout(HANDSHAKE_TIMEOUT, socket.recv()).await {
+        Ok(Some(frame)) => match Incoming::read(frame) {
+            Incoming::Message(shared::FromPlayer::Hello { version, features })
+                if version == shared::PROTOCOL_VERSION =>
+            {
+                let welcome = shared::ToPlayer::Welcome {
+                    version,
+                    features: shared::FEATURES.to_vec(),
+                };
+                let welcome = to_frame(&welcome, Encoding::Json)?;
+                socket.send(welcome).await.ok()?;
+                return Some(Encoding::negotiate(&features));
+            }
+            Incoming::Message(shared::FromPlayer::Hello { version, .. }) => {
+                shared::ErrorMessage::UnsupportedVersion {
+                    client: version,
+                    server: shared::PROTOCOL_VERSION,
+                }
+            }
+            Incoming::Closed => return None,
+            _ => shared::ErrorMessage::HelloExpected,
+        },
+        Ok(None) => return None,
+        Err(_) => shared::ErrorMessage::HelloExpected,
+    };
+    println!("Refusing client: {:?}", error);
+    if let Some(error) = to_frame(&shared::ToPlayer::Error(error), Encoding::Json) {
+        socket.send(error).await.ok();
+    }
+    socket.send(ws::Message::Close(None)).await.ok();
+    None
+}
+
+/// Frame received from a client.
+enum Incoming {
+    Message(shared::FromPlayer),
+    Ping(Vec<u8>),
+    /// Frame that could not be understood, with the reason.
+    Invalid(String),
+    Ignored,
+    Closed,
+}
+
+impl Incoming {
+    fn read(frame: Result<ws::Message, axum::Error>) -> Self {
+        match frame {
+            Ok(ws::Message::Text(text)) => Self::decode(Frame::Text(text)),
+            Ok(ws::Message::Binary(bytes)) => Self::decode(Frame::Binary(bytes)),
+            Ok(ws::Message::Ping(data)) => Incoming::Ping(data),
+            Ok(ws::Message::Pong(_)) => Incoming::Ignored,
+            Ok(ws::Message::Close(_)) => Incoming::Closed,
+            Err(e) => {
+                println!("WebSocket error: {:?}", e);
+                Incoming::Closed
+            }
+        }
+    }
+
+    fn decode(frame: Frame) -> Self {
+        match frame.decode() {
+            Ok(message) => Incoming::Message(message),
+            Err(e) => Incoming::Invalid(e),
+        }
+    }
 }
 
 #[tokio::main(flavor = "current_thread")]
 async fn main() {
-    let room = Arc::new(Mutex::new(Room::new()));
+    let omniscient_dead = std::env::var("OMNISCIENT_DEAD").is_ok_and(|v| v == "1");
+    let rooms = Arc::new(Mutex::new(rooms::Rooms::new(omniscient_dead)));
+    tokio::spawn(rooms::Rooms::cleanup(Arc::clone(&rooms)));
 
     let api_routes = Router::new()
         .route(
-            "/join",
+            "/rooms",
             get({
-                let room = Arc::clone(&room);
-                move |ws| Room::register_player(room, ws)
+                let rooms = Arc::clone(&rooms);
+                move || rooms::Rooms::list(rooms)
+            })
+            .post({
+                let rooms = Arc::clone(&rooms);
+                move || rooms::Rooms::create(rooms)
             }),
         )
         .route(
-            "/start",
+            "/rooms/:code/join",
             get({
-                let room = Arc::clone(&room);
-                move || Room::start(room)
+                let rooms = Arc::clone(&rooms);
+                move |code, params, ws| rooms::Rooms::join(rooms, code, params, ws)
+            }),
+        )
+        .route(
+            "/rooms/:code/reconnect",
+            get({
+                let rooms = Arc::clone(&rooms);
+                move |code, params, ws| rooms::Rooms::reconnect(rooms, code, params, ws)
+            }),
+        )
+        .route(
+            "/rooms/:code/spectate",
+            get({
+                let rooms = Arc::clone(&rooms);
+                move |code, params, ws| rooms::Rooms::spectate(rooms, code, params, ws)
+            }),
+        )
+        .route(
+            "/rooms/:code/bots",
+            post({
+                let rooms = Arc::clone(&rooms);
+                move |code, params| rooms::Rooms::add_bot(rooms, code, params)
+            }),
+        )
+        .route(
+            "/rooms/:code/start",
+            post({
+                let rooms = Arc::clone(&rooms);
+                move |code, params| rooms::Rooms::start(rooms, code, params)
             }),
         );
 
@@ -88,174 +552,694 @@ async fn main() {
 }
 
 impl Room {
-    async fn start(room: Arc<Mutex<Self>>) -> StatusCode {
-        let player_count = {
-            let mut room = room.lock().await;
-            room.state = RoomState::Running;
-            room.players.len()
+    /// Starts the game on behalf of the host, once every player is ready.
+    async fn start(
+        room: Arc<Mutex<Self>>,
+        Query(params): Query<StartParams>,
+    ) -> Result<StatusCode, (StatusCode, String)> {
+        // Custom boards can be provided as a TOML file, see the engine's
+        // `base.toml` for the format
+        let board = match std::env::var("BOARD_FILE") {
+            Ok(path) => engine::Board::load(path).map_err(|e| {
+                println!("{:?}", e);
+                (
+                    StatusCode::INTERNAL_SERVER_ERROR,
+                    "Invalid board file".to_owned(),
+                )
+            })?,
+            Err(_) => engine::Board::base(),
+        };
+
+        // Comma separated list of the packs to deal characters from
+        let packs = match params.packs.as_deref() {
+            Some(packs) => packs
+                .split(',')
+                .map(str::parse)
+                .collect::<Result<_, _>>()
+                .map_err(|e| (StatusCode::BAD_REQUEST, e))?,
+            None => vec![engine::state::Pack::Base],
         };
 
         let (tx, rx) = mpsc::channel(1);
-        let mut sh = engine::GameLogic::new(player_count, tx);
-        tokio::spawn(async move { sh.run().await });
-        tokio::spawn(async move { Self::run(room, rx).await });
+        let mut room_guard = room.lock().await;
+        if params.token != room_guard.host_token {
+            return Err((
+                StatusCode::FORBIDDEN,
+                "Only the host can start the game".to_owned(),
+            ));
+        }
+        if room_guard.state != RoomState::Registration {
+            return Err((StatusCode::CONFLICT, "The game already started".to_owned()));
+        }
+        if !engine::PLAYER_COUNTS.contains(&room_guard.players.len()) {
+            return Err((
+                StatusCode::UNPROCESSABLE_ENTITY,
+                format!(
+                    "The game needs {} to {} players",
+                    engine::PLAYER_COUNTS.start(),
+                    engine::PLAYER_COUNTS.end()
+                ),
+            ));
+        }
+        if let Some(p) = room_guard.players.iter().find(|p| !p.ready) {
+            return Err((StatusCode::CONFLICT, format!("{} is not ready", p.name)));
+        }
+        let mut sh = engine::GameLogic::new(room_guard.names(), packs, board, tx)
+            .map_err(|e| (StatusCode::UNPROCESSABLE_ENTITY, e.to_string()))?;
+        room_guard.state = RoomState::Running;
+        // Players who lost their connection in the lobby have the usual delay
+        // to come back
+        for p in room_guard.players.iter().filter(|p| !p.connected && !p.bot) {
+            Self::schedule_time_out(Arc::clone(&room), p.key);
+        }
+        room_guard.tasks.push(tokio::spawn(async move {
+            match sh.run().await {
+                Ok(winners) => println!("Game won by {:?}", winners),
+                Err(e) => println!("Game stopped: {:?}", e),
+            }
+        }));
+        room_guard.tasks.push(tokio::spawn({
+            let room = Arc::clone(&room);
+            async move { Self::run(room, rx).await }
+        }));
 
-        StatusCode::OK
+        Ok(StatusCode::OK)
     }
 
     async fn run(room: Arc<Mutex<Self>>, mut rx: mpsc::Receiver<engine::Command>) {
         while let Some(message) = rx.recv().await {
             match message {
                 engine::Command::ActionRequest {
+                    id,
                     player,
+                    prompt,
                     choices,
                     response,
                 } => {
                     let mut room = room.lock().await;
 
                     let p = room.get_player_mut(player);
-                    p.tx.send(PlayerMessage::ActionRequest { choices })
-                        .await
-                        .unwrap();
-                    assert!(p.request_answer.is_none());
-                    p.request_answer = Some(response)
+                    // The engine never prompts dead players, as its
+                    // `dead_players_are_never_prompted` test checks
+                    debug_assert!(!p.dead, "{:?} requested from dead {:?}", id, player);
+                    if p.bot {
+                        response.send(bot::choose(player, &prompt, &choices)).ok();
+                        continue;
+                    }
+                    let request = PendingRequest {
+                        id,
+                        prompt,
+                        choices,
+                        response,
+                    };
+                    p.send(request.message());
+                    p.pending_requests.push(request);
+                    room.broadcast_waiting();
                 }
                 engine::Command::Info {
                     destination,
                     payload,
                 } => {
                     let mut room = room.lock().await;
-                    for p in room
-                        .players
-                        .iter_mut()
-                        .filter(|p| destination.contains(&p.id))
-                    {
-                        p.tx.send(PlayerMessage::Info {
-                            payload: payload.clone(),
-                        })
-                        .await
-                        .unwrap();
-                    }
+                    let public = room.players.iter().all(|p| destination.contains(&p.id));
+                    let audience = if public { None } else { Some(destination) };
+                    room.publish(audience, PlayerMessage::Info { payload });
                 }
                 engine::Command::StateMutation(mutation) => {
                     let mut room = room.lock().await;
+                    room.game_state
+                        .as_mut()
+                        .expect("Mutation received before initial state")
+                        .mutate(mutation);
                     for p in &mut room.players {
-                        p.tx.send(PlayerMessage::StateMutation(mutation))
-                            .await
-                            .unwrap();
+                        p.send(PlayerMessage::StateMutation(mutation));
+                    }
+                    for s in &room.spectators {
+                        s.send(PlayerMessage::StateMutation(mutation));
+                    }
+                    if let engine::state::Mutation::Death(player, _) = mutation {
+                        room.get_player_mut(player).dead = true;
+                        if room.omniscient_dead {
+                            room.sync_player(player);
+                        }
+                    }
+                }
+                engine::Command::StateSync(state) => {
+                    let mut room = room.lock().await;
+                    room.game_state = Some(*state);
+                    for i in 0..room.players.len() {
+                        let id = room.players[i].id;
+                        room.sync_player(id);
+                    }
+                    for s in &room.spectators {
+                        room.sync_spectator(s);
                     }
                 }
             }
         }
+        // The game is over once the engine stops sending commands
+        room.lock().await.finish();
+    }
+
+    fn broadcast_waiting(&mut self) {
+        let waiting = self
+            .players
+            .iter()
+            .filter(|p| !p.pending_requests.is_empty())
+            .map(|p| p.id)
+            .collect::<Vec<_>>();
+        for p in &mut self.players {
+            p.send(PlayerMessage::WaitingFor(waiting.clone()));
+        }
+        for s in &self.spectators {
+            s.send(PlayerMessage::WaitingFor(waiting.clone()));
+        }
+    }
+
+    fn sync_player(&mut self, id: PlayerId) {
+        let omniscient = self.omniscient_dead && self.get_player_mut(id).dead;
+        let state = match &self.game_state {
+            Some(state) if omniscient => Box::new(state.clone()),
+            Some(state) => Box::new(state.prepare_for_player(id)),
+            None => return,
+        };
+        self.get_player_mut(id)
+            .send(PlayerMessage::StateSync(state));
+    }
+
+    /// Sends a message to its audience, everyone if `None`, and records it
+    /// in the game log once the game started, lobby ids changing as players
+    /// leave. Spectators only receive public messages, unless omniscient.
+    fn publish(&mut self, audience: Option<Vec<PlayerId>>, message: PlayerMessage) {
+        let entry = LogEntry { audience, message };
+        for p in self.players.iter().filter(|p| entry.is_for(p.id)) {
+            p.send(entry.message.clone());
+        }
+        for s in &self.spectators {
+            if entry.audience.is_none() || s.omniscient {
+                s.send(entry.message.clone());
+            }
+        }
+        if self.state != RoomState::Registration {
+            self.log.push(entry);
+        }
+    }
+
+    fn replay_log(&self, id: PlayerId) {
+        let p = self
+            .players
+            .iter()
+            .find(|p| p.id == id)
+            .expect("Invalid player id");
+        for entry in self.log.iter().filter(|e| e.is_for(id)) {
+            p.send(entry.message.clone());
+        }
+    }
+
+    /// Relays a chat message, provided it is not too long, its recipient
+    /// exists and its sender does not talk too much. Dead players seeing
+    /// every character may not chat at all.
+    fn chat(&mut self, from: PlayerId, channel: shared::ChatChannel, text: String) {
+        let text = text.trim().to_owned();
+        if text.is_empty() {
+            return;
+        }
+        let error = match channel {
+            _ if text.chars().count() > MAX_CHAT_LENGTH => {
+                Some(shared::ErrorMessage::ChatTooLong {
+                    max: MAX_CHAT_LENGTH,
+                })
+            }
+            shared::ChatChannel::Private(to) if !self.players.iter().any(|p| p.id == to) => {
+                Some(shared::ErrorMessage::UnknownPlayer(to))
+            }
+            _ if self.omniscient_dead && self.get_player_mut(from).dead => {
+                Some(shared::ErrorMessage::ChatForbidden)
+            }
+            _ if !self.get_player_mut(from).may_chat() => {
+                Some(shared::ErrorMessage::ChatRateLimited)
+            }
+            _ => None,
+        };
+        if let Some(error) = error {
+            self.get_player_mut(from).send(PlayerMessage::Error(error));
+            return;
+        }
+        let audience = match channel {
+            shared::ChatChannel::Table => None,
+            shared::ChatChannel::Private(to) => Some(vec![from, to]),
+        };
+        self.publish(
+            audience,
+            PlayerMessage::Chat {
+                from,
+                channel,
+                text,
+            },
+        );
     }
 
-    async fn register_player(room: Arc<Mutex<Room>>, ws: WebSocketUpgrade) -> Response {
-        let (tx, rx) = mpsc::channel(10);
-        let id = {
+    /// Spectators only know revealed characters, unless omniscient.
+    fn sync_spectator(&self, spectator: &Spectator) {
+        let state = match &self.game_state {
+            Some(state) if spectator.omniscient => Box::new(state.clone()),
+            Some(state) => Box::new(state.prepare_for_spectator()),
+            None => return,
+        };
+        spectator.send(PlayerMessage::StateSync(state));
+    }
+
+    fn names(&self) -> Vec<String> {
+        self.players.iter().map(|p| p.name.clone()).collect()
+    }
+
+    fn broadcast_roster(&mut self) {
+        let players = self
+            .players
+            .iter()
+            .map(|p| shared::PlayerProfile {
+                id: p.id,
+                name: p.name.clone(),
+                color: p.color.clone(),
+                ready: p.ready,
+                connected: p.connected,
+                bot: p.bot,
+            })
+            .collect::<Vec<_>>();
+        for p in &mut self.players {
+            p.send(PlayerMessage::Roster {
+                players: players.clone(),
+                you: p.id,
+            });
+        }
+    }
+
+    async fn register_player(
+        room: Arc<Mutex<Room>>,
+        Query(params): Query<JoinParams>,
+        ws: WebSocketUpgrade,
+    ) -> Response {
+        let name = params.name.trim().to_owned();
+        if name.is_empty() || name.chars().count() > MAX_NAME_LENGTH {
+            let message = format!("Names must have 1 to {} characters", MAX_NAME_LENGTH);
+            return (StatusCode::BAD_REQUEST, message).into_response();
+        }
+        let (tx, rx) = mpsc::unbounded_channel();
+        let key = {
             let mut room = room.lock().await;
-            if let RoomState::Running = room.state {
-                return StatusCode::NOT_FOUND.into_response();
+            if room.state != RoomState::Registration {
+                return (StatusCode::CONFLICT, "The game already started").into_response();
             }
+            if room.players.iter().any(|p| p.name == name) {
+                return (StatusCode::CONFLICT, "Name already taken").into_response();
+            }
+            if room.players.len() >= *engine::PLAYER_COUNTS.end() {
+                return (StatusCode::CONFLICT, "The room is full").into_response();
+            }
+            let key = room.next_key;
+            room.next_key += 1;
             let id = PlayerId::new(room.players.len());
-            room.players.push(Player::new(id, tx));
-            id
+            let token = rooms::random_token();
+            let player = Player::new(key, id, name, params.color, token.clone(), tx);
+            player.send(PlayerMessage::SessionToken(token));
+            room.players.push(player);
+            room.broadcast_roster();
+            key
         };
-        ws.on_upgrade(move |socket| Self::handle_player(room, id, socket, rx))
+        ws.on_upgrade(move |socket| Self::handle_player(room, key, socket, rx))
+    }
+
+    /// Adds a seat played by the server, on behalf of the host.
+    async fn add_bot(
+        room: Arc<Mutex<Room>>,
+        Query(params): Query<HostParams>,
+    ) -> Result<StatusCode, (StatusCode, String)> {
+        let mut room = room.lock().await;
+        if params.token != room.host_token {
+            return Err((
+                StatusCode::FORBIDDEN,
+                "Only the host can add bots".to_owned(),
+            ));
+        }
+        if room.state != RoomState::Registration {
+            return Err((StatusCode::CONFLICT, "The game already started".to_owned()));
+        }
+        if room.players.len() >= *engine::PLAYER_COUNTS.end() {
+            return Err((StatusCode::CONFLICT, "The room is full".to_owned()));
+        }
+        let key = room.next_key;
+        room.next_key += 1;
+        let id = PlayerId::new(room.players.len());
+        let name = format!("Bot {}", room.players.iter().filter(|p| p.bot).count() + 1);
+        room.players.push(Player::new_bot(key, id, name));
+        room.broadcast_roster();
+        Ok(StatusCode::OK)
+    }
+
+    /// Gives its seat back to a player who lost its connection, and sends it
+    /// everything it needs to resume the game.
+    async fn reconnect_player(
+        room: Arc<Mutex<Room>>,
+        Query(params): Query<ReconnectParams>,
+        ws: WebSocketUpgrade,
+    ) -> Response {
+        let (tx, rx) = mpsc::unbounded_channel();
+        let key = {
+            let mut room = room.lock().await;
+            let key = room.next_key;
+            let Some(p) = room.players.iter_mut().find(|p| p.token == params.token) else {
+                return (StatusCode::NOT_FOUND, "Unknown session").into_response();
+            };
+            // The previous connection, if still open, is now ignored
+            p.key = key;
+            p.tx = tx;
+            p.connected = true;
+            p.bot = false;
+            let id = p.id;
+            room.next_key += 1;
+            println!("{:?} reconnected", id);
+
+            room.broadcast_roster();
+            room.replay_log(id);
+            room.sync_player(id);
+            let p = room.get_player_mut(id);
+            for request in &p.pending_requests {
+                p.send(request.message());
+            }
+            room.broadcast_waiting();
+            key
+        };
+        ws.on_upgrade(move |socket| Self::handle_player(room, key, socket, rx))
+    }
+
+    /// Lets anyone follow the game, only receiving public information
+    /// unless omniscient.
+    async fn add_spectator(
+        room: Arc<Mutex<Room>>,
+        Query(params): Query<SpectateParams>,
+        ws: WebSocketUpgrade,
+    ) -> Response {
+        let (tx, rx) = mpsc::unbounded_channel();
+        let key = {
+            let mut room = room.lock().await;
+            if let Err(e) = room.authorize_spectator(&params) {
+                return e.into_response();
+            }
+            let key = room.next_key;
+            room.next_key += 1;
+            let spectator = Spectator {
+                key,
+                tx,
+                omniscient: params.omniscient,
+            };
+            for entry in &room.log {
+                if entry.audience.is_none() || spectator.omniscient {
+                    spectator.send(entry.message.clone());
+                }
+            }
+            room.sync_spectator(&spectator);
+            room.spectators.push(spectator);
+            key
+        };
+        let delay = if params.omniscient {
+            OMNISCIENT_DELAY
+        } else {
+            Duration::ZERO
+        };
+        ws.on_upgrade(move |socket| Self::handle_spectator(room, key, socket, rx, delay))
+    }
+
+    /// Omniscient spectators see every hand, so only the host may watch that
+    /// way before the game is over.
+    fn authorize_spectator(&self, params: &SpectateParams) -> Result<(), (StatusCode, String)> {
+        if !params.omniscient
+            || self.state == RoomState::Finished
+            || params.token.as_deref() == Some(self.host_token.as_str())
+        {
+            return Ok(());
+        }
+        Err((
+            StatusCode::FORBIDDEN,
+            "Only the host can watch hidden information before the end".to_owned(),
+        ))
+    }
+
+    async fn handle_spectator(
+        room: Arc<Mutex<Self>>,
+        key: usize,
+        mut socket: WebSocket,
+        mut receiver: mpsc::UnboundedReceiver<(Instant, PlayerMessage)>,
+        delay: Duration,
+    ) {
+        let Some(encoding) = handshake(&mut socket).await else {
+            room.lock().await.remove_spectator(key);
+            return;
+        };
+        let (socket_tx, socket_rx) = socket.split();
+        let (tx, rx) = mpsc::unbounded_channel();
+        let forward = {
+            let tx = tx.clone();
+            async move {
+                while let Some((emitted, message)) = receiver.recv().await {
+                    // Closing the room is not delayed
+                    if !matches!(message, PlayerMessage::Close) {
+                        tokio::time::sleep_until((emitted + delay).into()).await;
+                    }
+                    if tx.send(message).is_err() {
+                        break;
+                    }
+                }
+            }
+        };
+        tokio::select! {
+            _ = forward => {}
+            _ = Self::handle_spectator_ws(&room, key, socket_rx, tx) => {}
+            _ = Self::handle_player_commands(rx, socket_tx, encoding) => {}
+        }
+        room.lock().await.remove_spectator(key);
+    }
+
+    async fn handle_spectator_ws(
+        room: &Mutex<Self>,
+        key: usize,
+        mut socket: SplitStream<WebSocket>,
+        tx: mpsc::UnboundedSender<PlayerMessage>,
+    ) {
+        while let Some(frame) = socket.next().await {
+            let message = match Incoming::read(frame) {
+                // Spectators have nothing to answer, but may need a sync
+                Incoming::Message(shared::FromPlayer::RequestStateSync) => {
+                    let room = room.lock().await;
+                    if let Some(s) = room.spectators.iter().find(|s| s.key == key) {
+                        room.sync_spectator(s);
+                    }
+                    continue;
+                }
+                Incoming::Message(_) => continue,
+                Incoming::Ping(data) => PlayerMessage::Pong(data),
+                Incoming::Invalid(reason) => {
+                    println!("Invalid message from spectator {}: {}", key, reason);
+                    PlayerMessage::Error(shared::ErrorMessage::InvalidMessage(reason))
+                }
+                Incoming::Ignored => continue,
+                Incoming::Closed => break,
+            };
+            if tx.send(message).is_err() {
+                break;
+            }
+        }
     }
 
     async fn handle_player(
         room: Arc<Mutex<Self>>,
-        id: PlayerId,
-        socket: WebSocket,
-        receiver: mpsc::Receiver<PlayerMessage>,
+        key: usize,
+        mut socket: WebSocket,
+        receiver: mpsc::UnboundedReceiver<PlayerMessage>,
     ) {
+        let Some(encoding) = handshake(&mut socket).await else {
+            // Clients which never got in do not keep a seat in the lobby
+            if room.lock().await.state == RoomState::Registration {
+                Self::leave(room, key).await;
+            } else {
+                Self::disconnect(room, key).await;
+            }
+            return;
+        };
         let (socket_tx, socket_rx) = socket.split();
-        {
-            let room = Arc::clone(&room);
-            tokio::spawn(async move { Self::handle_player_ws(room, id, socket_rx).await });
+        // The connection is over as soon as either direction fails
+        let left = tokio::select! {
+            left = Self::handle_player_ws(&room, key, socket_rx) => left,
+            _ = Self::handle_player_commands(receiver, socket_tx, encoding) => false,
+        };
+        if left {
+            Self::leave(room, key).await;
+        } else {
+            Self::disconnect(room, key).await;
         }
-        Self::handle_player_commands(receiver, socket_tx).await;
     }
 
+    /// Writes messages to the socket until the channel is closed, which
+    /// happens when a newer connection replaces this one, until the room
+    /// closes the connection, or until the socket is found dead.
     async fn handle_player_commands(
-        mut receiver: mpsc::Receiver<PlayerMessage>,
+        mut receiver: mpsc::UnboundedReceiver<PlayerMessage>,
         mut socket: SplitSink<WebSocket, ws::Message>,
+        encoding: Encoding,
     ) {
-        while let Some(msg) = receiver.recv().await {
-            match msg {
-                PlayerMessage::ActionRequest { choices } => {
-                    socket
-                        .send(ws::Message::Text(
-                            serde_json::to_string(&shared::ToPlayer::ActionRequest { choices })
-                                .unwrap(),
-                        ))
-                        .await
-                        .unwrap();
-                }
-                PlayerMessage::Info { payload } => {
-                    socket
-                        .send(ws::Message::Text(
-                            serde_json::to_string(&shared::ToPlayer::Info { payload }).unwrap(),
-                        ))
-                        .await
-                        .unwrap();
-                }
-                PlayerMessage::StateMutation(mutation) => {
-                    socket
-                        .send(ws::Message::Text(
-                            serde_json::to_string(&shared::ToPlayer::StateMutation(mutation))
-                                .unwrap(),
-                        ))
-                        .await
-                        .unwrap();
+        let mut heartbeat = tokio::time::interval(HEARTBEAT_INTERVAL);
+        let mut closing = false;
+        while !closing {
+            let frame = tokio::select! {
+                message = receiver.recv() => match message {
+                    Some(message) => {
+                        closing = matches!(message, PlayerMessage::Close);
+                        match message.into_frame(encoding) {
+                            Some(frame) => frame,
+                            None => continue,
+                        }
+                    }
+                    None => break,
+                },
+                _ = heartbeat.tick() => ws::Message::Ping(vec![]),
+            };
+            match tokio::time::timeout(SEND_TIMEOUT, socket.send(frame)).await {
+                Ok(Ok(())) => {}
+                Ok(Err(e)) => {
+                    println!("Closing connection: {:?}", e);
+                    break;
                 }
-                PlayerMessage::Pong(data) => {
-                    socket.send(ws::Message::Pong(data)).await.unwrap();
+                Err(_) => {
+                    println!("Closing stuck connection");
+                    break;
                 }
             }
         }
     }
 
+    /// Reads the messages of a player until its connection is closed, and
+    /// returns whether the player left the room.
     async fn handle_player_ws(
-        room: Arc<Mutex<Self>>,
-        id: PlayerId,
+        room: &Mutex<Self>,
+        key: usize,
         mut socket: SplitStream<WebSocket>,
-    ) {
-        while let Some(msg) = socket.next().await {
-            match msg {
-                Ok(msg) => match msg {
-                    ws::Message::Text(text) => {
-                        let msg: shared::FromPlayer = serde_json::from_str(&text).unwrap();
-                        match msg {
-                            shared::FromPlayer::ActionChoice(choice) => {
-                                let mut room = room.lock().await;
-                                if let Some(tx) = room.get_player_mut(id).request_answer.take() {
-                                    tx.send(choice).unwrap();
-                                } else {
-                                    println!("Did not expect a choice from {:?}", id);
-                                }
-                            }
-                        }
-                    }
-                    ws::Message::Close(_) => {
-                        println!("WebSocket closed");
-                        return;
-                    }
-                    ws::Message::Ping(data) => {
-                        let mut room = room.lock().await;
-                        room.get_player_mut(id)
-                            .tx
-                            .send(PlayerMessage::Pong(data))
-                            .await
-                            .unwrap();
+    ) -> bool {
+        while let Some(frame) = socket.next().await {
+            let mut room = room.lock().await;
+            let Some(id) = room.player_id(key) else {
+                break;
+            };
+            match Incoming::read(frame) {
+                Incoming::Message(shared::FromPlayer::ActionChoice { request, choice }) => {
+                    if room.get_player_mut(id).answer(request, choice) {
+                        room.broadcast_waiting();
                     }
-                    m => {
-                        println!("{:?}", m);
+                }
+                Incoming::Message(shared::FromPlayer::RequestStateSync) => {
+                    room.sync_player(id);
+                }
+                Incoming::Message(shared::FromPlayer::Hello { .. }) => {
+                    let error = shared::ErrorMessage::InvalidMessage("Already greeted".to_owned());
+                    room.get_player_mut(id).send(PlayerMessage::Error(error));
+                }
+                Incoming::Message(shared::FromPlayer::Chat { channel, text }) => {
+                    room.chat(id, channel, text);
+                }
+                Incoming::Message(shared::FromPlayer::Ready(ready)) => {
+                    if room.state == RoomState::Registration {
+                        room.get_player_mut(id).ready = ready;
+                        room.broadcast_roster();
                     }
-                },
-                Err(e) => println!("{:?}", e),
+                }
+                Incoming::Message(shared::FromPlayer::Leave) => return true,
+                Incoming::Ping(data) => {
+                    room.get_player_mut(id).send(PlayerMessage::Pong(data));
+                }
+                Incoming::Invalid(reason) => {
+                    println!("Invalid message from {:?}: {}", id, reason);
+                    let error = shared::ErrorMessage::InvalidMessage(reason);
+                    room.get_player_mut(id).send(PlayerMessage::Error(error));
+                }
+                Incoming::Ignored => {}
+                Incoming::Closed => {
+                    println!("{:?} closed its connection", id);
+                    break;
+                }
             }
         }
+        false
+    }
+
+    fn player_id(&self, key: usize) -> Option<PlayerId> {
+        self.players.iter().find(|p| p.key == key).map(|p| p.id)
+    }
+
+    /// Players losing their connection keep their seat, marked as
+    /// disconnected, so that they can reconnect. Once the game started, a
+    /// bot takes the seat if they do not come back in time.
+    async fn disconnect(room: Arc<Mutex<Self>>, key: usize) {
+        let mut guard = room.lock().await;
+        guard.last_connected = Instant::now();
+        let Some(p) = guard.players.iter_mut().find(|p| p.key == key) else {
+            // Replaced by a newer connection
+            return;
+        };
+        p.connected = false;
+        println!("{:?} disconnected", p.id);
+        let running = guard.state != RoomState::Registration;
+        guard.broadcast_roster();
+        drop(guard);
+
+        if running {
+            Self::schedule_time_out(room, key);
+        }
+    }
+
+    fn schedule_time_out(room: Arc<Mutex<Self>>, key: usize) {
+        tokio::spawn(async move {
+            tokio::time::sleep(RECONNECT_TIMEOUT).await;
+            room.lock().await.time_out(key);
+        });
+    }
+
+    /// Players leaving the lobby are removed from the room, the following
+    /// ones moving up. Once the game started, a bot takes their seat at
+    /// once.
+    async fn leave(room: Arc<Mutex<Self>>, key: usize) {
+        let mut guard = room.lock().await;
+        guard.last_connected = Instant::now();
+        if guard.state == RoomState::Registration {
+            guard.players.retain(|p| p.key != key);
+            for (i, p) in guard.players.iter_mut().enumerate() {
+                p.id = PlayerId::new(i);
+            }
+            guard.broadcast_roster();
+            return;
+        }
+        let Some(p) = guard.players.iter_mut().find(|p| p.key == key) else {
+            return;
+        };
+        p.connected = false;
+        println!("{:?} left", p.id);
+        guard.time_out(key);
+    }
+
+    /// Hands the seat of a player to a bot, unless it reconnected.
+    fn time_out(&mut self, key: usize) {
+        let Some(p) = self
+            .players
+            .iter_mut()
+            .find(|p| p.key == key && !p.connected)
+        else {
+            return;
+        };
+        println!("{:?} timed out, a bot takes its seat", p.id);
+        p.bot = true;
+        for request in p.pending_requests.drain(..) {
+            let choice = bot::choose(p.id, &request.prompt, &request.choices);
+            request.response.send(choice).ok();
+        }
+        self.broadcast_roster();
+        self.broadcast_waiting();
     }
 
     fn get_player_mut(&mut self, id: PlayerId) -> &mut Player {
@@ -265,3 +1249,165 @@ impl Room {
             .expect("Invalid player id")
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Seats a connected player and returns what it receives.
+    fn join(room: &mut Room) -> mpsc::UnboundedReceiver<PlayerMessage> {
+        let (tx, rx) = mpsc::unbounded_channel();
+        let (key, id) = (room.next_key, PlayerId::new(room.players.len()));
+        room.next_key += 1;
+        let name = format!("Player {}", key);
+        room.players
+            .push(Player::new(key, id, name, None, rooms::random_token(), tx));
+        rx
+    }
+
+    fn abandoned_for(room: &mut Room, delay: Duration) {
+        room.last_connected = Instant::now().checked_sub(delay).unwrap();
+    }
+
+    #[test]
+    fn rooms_are_over_a_while_after_the_end_or_once_abandoned() {
+        let mut room = Room::new(false, rooms::random_token());
+        assert!(!room.is_over());
+        abandoned_for(&mut room, ABANDON_DELAY * 2);
+        assert!(room.is_over());
+
+        // Connected players keep the room open, whenever they arrived
+        let _rx = join(&mut room);
+        assert!(!room.is_over());
+
+        // Bots do not
+        room.players[0].connected = false;
+        room.players[0].bot = true;
+        assert!(room.is_over());
+
+        // The delay counts from the last connection
+        abandoned_for(&mut room, ABANDON_DELAY / 2);
+        assert!(!room.is_over());
+
+        // Finished rooms stay open for a while, counted from the end
+        room.finish();
+        assert!(!room.is_over());
+        room.finished = Instant::now().checked_sub(FINISHED_DELAY * 2);
+        assert!(room.is_over());
+    }
+
+    #[tokio::test]
+    async fn lobby_seats_are_kept_until_players_leave() {
+        let room = Arc::new(Mutex::new(Room::new(false, rooms::random_token())));
+        let (_first, _second) = {
+            let mut room = room.lock().await;
+            (join(&mut room), join(&mut room))
+        };
+        let (first, second) = {
+            let room = room.lock().await;
+            (room.players[0].key, room.players[1].key)
+        };
+
+        Room::disconnect(Arc::clone(&room), first).await;
+        {
+            let room = room.lock().await;
+            assert_eq!(room.players.len(), 2);
+            assert!(!room.players[0].connected);
+        }
+
+        Room::leave(Arc::clone(&room), first).await;
+        let room = room.lock().await;
+        assert_eq!(room.players.len(), 1);
+        assert_eq!(room.players[0].key, second);
+        assert_eq!(room.players[0].id, PlayerId::new(0));
+    }
+
+    #[tokio::test]
+    async fn bots_take_the_seats_of_players_leaving_the_game() {
+        let room = Arc::new(Mutex::new(Room::new(false, rooms::random_token())));
+        let _rx = join(&mut *room.lock().await);
+        room.lock().await.state = RoomState::Running;
+        let key = room.lock().await.players[0].key;
+
+        Room::leave(Arc::clone(&room), key).await;
+        let room = room.lock().await;
+        assert!(room.players[0].bot);
+        assert!(!room.players[0].connected);
+    }
+
+    #[test]
+    fn omniscient_dead_players_may_not_chat() {
+        let mut room = Room::new(true, rooms::random_token());
+        let mut dead = join(&mut room);
+        let mut alive = join(&mut room);
+        room.players[0].dead = true;
+
+        room.chat(
+            PlayerId::new(0),
+            shared::ChatChannel::Table,
+            "Hi".to_owned(),
+        );
+        assert!(matches!(
+            dead.try_recv(),
+            Ok(PlayerMessage::Error(shared::ErrorMessage::ChatForbidden))
+        ));
+        assert!(alive.try_recv().is_err());
+
+        // Without seeing every character, dead players may talk
+        room.omniscient_dead = false;
+        room.chat(
+            PlayerId::new(0),
+            shared::ChatChannel::Table,
+            "Hi".to_owned(),
+        );
+        assert!(matches!(alive.try_recv(), Ok(PlayerMessage::Chat { .. })));
+    }
+
+    #[test]
+    fn omniscient_spectators_need_the_host_token_until_the_end() {
+        let mut room = Room::new(false, "host".to_owned());
+        let params = |omniscient, token: Option<&str>| SpectateParams {
+            omniscient,
+            token: token.map(str::to_owned),
+        };
+
+        assert!(room.authorize_spectator(&params(false, None)).is_ok());
+        let refused = room.authorize_spectator(&params(true, None)).unwrap_err();
+        assert_eq!(refused.0, StatusCode::FORBIDDEN);
+        assert!(room
+            .authorize_spectator(&params(true, Some("guess")))
+            .is_err());
+        assert!(room
+            .authorize_spectator(&params(true, Some("host")))
+            .is_ok());
+
+        room.finish();
+        assert!(room.authorize_spectator(&params(true, None)).is_ok());
+    }
+
+    #[tokio::test]
+    async fn closing_rooms_closes_connections() {
+        let mut room = Room::new(false, rooms::random_token());
+        let mut player = join(&mut room);
+        let (tx, mut spectator) = mpsc::unbounded_channel();
+        room.spectators.push(Spectator {
+            key: room.next_key,
+            tx,
+            omniscient: false,
+        });
+        let game = tokio::spawn(std::future::pending::<()>());
+        room.tasks.push(game);
+
+        room.close();
+        assert!(room.tasks.is_empty());
+        assert!(matches!(player.recv().await, Some(PlayerMessage::Close)));
+        assert!(matches!(
+            spectator.recv().await,
+            Some((_, PlayerMessage::Close))
+        ));
+        assert!(matches!(
+            PlayerMessage::Close.into_frame(Encoding::Json),
+            Some(ws::Message::Close(None))
+        ));
+    }
+}
@@ -0,0 +1,195 @@
+use std::{collections::HashMap, sync::Arc, time::Duration};
+
+use axum::{
+    extract::{ws::WebSocketUpgrade, Path, Query},
+    http::StatusCode,
+    response::{IntoResponse, Response},
+    Json,
+};
+use rand::Rng;
+use tokio::sync::Mutex;
+
+use crate::{
+    HostParams, JoinParams, ReconnectParams, Room, RoomState, SpectateParams, StartParams,
+};
+
+/// Letters of the join codes, without the ones easily mistaken for others.
+const CODE_LETTERS: &[u8] = b"ABCDEFGHJKLMNPQRSTUVWXYZ";
+const CODE_LENGTH: usize = 4;
+const TOKEN_LETTERS: &[u8] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789";
+const TOKEN_LENGTH: usize = 24;
+/// How often rooms are checked for cleanup.
+const CLEANUP_INTERVAL: Duration = Duration::from_secs(60);
+
+#[derive(Debug, serde::Serialize)]
+pub(crate) struct RoomInfo {
+    code: String,
+    players: usize,
+    state: RoomState,
+}
+
+/// Answer to the creation of a room, the token being required to start the
+/// game.
+#[derive(Debug, serde::Serialize)]
+pub(crate) struct CreatedRoom {
+    #[serde(flatten)]
+    room: RoomInfo,
+    host_token: String,
+}
+
+pub(crate) fn random_token() -> String {
+    random_string(TOKEN_LETTERS, TOKEN_LENGTH)
+}
+
+fn random_string(letters: &[u8], length: usize) -> String {
+    let mut rng = rand::thread_rng();
+    (0..length)
+        .map(|_| letters[rng.gen_range(0..letters.len())] as char)
+        .collect()
+}
+
+/// Rooms hosted by the server, identified by the code players use to join
+/// them.
+pub(crate) struct Rooms {
+    rooms: HashMap<String, Arc<Mutex<Room>>>,
+    omniscient_dead: bool,
+}
+
+impl Rooms {
+    pub(crate) fn new(omniscient_dead: bool) -> Self {
+        Self {
+            rooms: HashMap::new(),
+            omniscient_dead,
+        }
+    }
+
+    fn generate_code(&self) -> String {
+        loop {
+            let code = random_string(CODE_LETTERS, CODE_LENGTH);
+            if !self.rooms.contains_key(&code) {
+                return code;
+            }
+        }
+    }
+
+    fn get(&self, code: &str) -> Option<Arc<Mutex<Room>>> {
+        self.rooms.get(&code.to_uppercase()).map(Arc::clone)
+    }
+
+    async fn info(code: &str, room: &Mutex<Room>) -> RoomInfo {
+        let room = room.lock().await;
+        RoomInfo {
+            code: code.to_owned(),
+            players: room.players.len(),
+            state: room.state,
+        }
+    }
+
+    pub(crate) async fn create(rooms: Arc<Mutex<Self>>) -> Json<CreatedRoom> {
+        let mut rooms = rooms.lock().await;
+        let code = rooms.generate_code();
+        let host_token = random_token();
+        let room = Room::new(rooms.omniscient_dead, host_token.clone());
+        let room = Arc::new(Mutex::new(room));
+        println!("Created room {}", code);
+        let info = Self::info(&code, &room).await;
+        rooms.rooms.insert(code, room);
+        Json(CreatedRoom {
+            room: info,
+            host_token,
+        })
+    }
+
+    pub(crate) async fn list(rooms: Arc<Mutex<Self>>) -> Json<Vec<RoomInfo>> {
+        let rooms = rooms.lock().await;
+        let mut infos = vec![];
+        for (code, room) in &rooms.rooms {
+            infos.push(Self::info(code, room).await);
+        }
+        Json(infos)
+    }
+
+    pub(crate) async fn join(
+        rooms: Arc<Mutex<Self>>,
+        Path(code): Path<String>,
+        params: Query<JoinParams>,
+        ws: WebSocketUpgrade,
+    ) -> Response {
+        let room = rooms.lock().await.get(&code);
+        match room {
+            Some(room) => Room::register_player(room, params, ws).await,
+            None => StatusCode::NOT_FOUND.into_response(),
+        }
+    }
+
+    pub(crate) async fn reconnect(
+        rooms: Arc<Mutex<Self>>,
+        Path(code): Path<String>,
+        params: Query<ReconnectParams>,
+        ws: WebSocketUpgrade,
+    ) -> Response {
+        let room = rooms.lock().await.get(&code);
+        match room {
+            Some(room) => Room::reconnect_player(room, params, ws).await,
+            None => StatusCode::NOT_FOUND.into_response(),
+        }
+    }
+
+    pub(crate) async fn spectate(
+        rooms: Arc<Mutex<Self>>,
+        Path(code): Path<String>,
+        params: Query<SpectateParams>,
+        ws: WebSocketUpgrade,
+    ) -> Response {
+        let room = rooms.lock().await.get(&code);
+        match room {
+            Some(room) => Room::add_spectator(room, params, ws).await,
+            None => StatusCode::NOT_FOUND.into_response(),
+        }
+    }
+
+    pub(crate) async fn add_bot(
+        rooms: Arc<Mutex<Self>>,
+        Path(code): Path<String>,
+        params: Query<HostParams>,
+    ) -> Result<StatusCode, (StatusCode, String)> {
+        let room = rooms.lock().await.get(&code);
+        match room {
+            Some(room) => Room::add_bot(room, params).await,
+            None => Err((StatusCode::NOT_FOUND, format!("No room {}", code))),
+        }
+    }
+
+    pub(crate) async fn start(
+        rooms: Arc<Mutex<Self>>,
+        Path(code): Path<String>,
+        params: Query<StartParams>,
+    ) -> Result<StatusCode, (StatusCode, String)> {
+        let room = rooms.lock().await.get(&code);
+        match room {
+            Some(room) => Room::start(room, params).await,
+            None => Err((StatusCode::NOT_FOUND, format!("No room {}", code))),
+        }
+    }
+
+    /// Regularly closes finished and abandoned rooms.
+    pub(crate) async fn cleanup(rooms: Arc<Mutex<Self>>) {
+        let mut interval = tokio::time::interval(CLEANUP_INTERVAL);
+        loop {
+            interval.tick().await;
+            let mut rooms = rooms.lock().await;
+            let mut closed = vec![];
+            for (code, room) in &rooms.rooms {
+                let mut room = room.lock().await;
+                if room.is_over() {
+                    room.close();
+                    closed.push(code.clone());
+                }
+            }
+            for code in closed {
+                println!("Closed room {}", code);
+                rooms.rooms.remove(&code);
+            }
+        }
+    }
+}
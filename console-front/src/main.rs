@@ -1,70 +1,299 @@
 use std::io::BufRead;
 
-use shared::FromPlayer;
-use tungstenite::connect;
+use shared::{
+    wire::{Encoding, Frame},
+    Feature, FromPlayer,
+};
+use tungstenite::{connect, stream::MaybeTlsStream};
 use url::Url;
 
+type Socket = tungstenite::WebSocket<MaybeTlsStream<std::net::TcpStream>>;
+
+/// Connects to the server and introduces the client, which the server
+/// expects before anything else.
+fn open(url: Url, encoding: Encoding) -> anyhow::Result<Socket> {
+    let (mut socket, _response) = connect(url)?;
+    let features = shared::FEATURES
+        .iter()
+        .copied()
+        .filter(|&f| f != Feature::MessagePack || encoding == Encoding::MessagePack)
+        .collect();
+    let hello = FromPlayer::Hello {
+        version: shared::PROTOCOL_VERSION,
+        features,
+    };
+    socket.write_message(to_message(&hello, Encoding::Json))?;
+    Ok(socket)
+}
+
+fn to_message(message: &FromPlayer, encoding: Encoding) -> tungstenite::Message {
+    match Frame::encode(message, encoding).unwrap() {
+        Frame::Text(text) => tungstenite::Message::Text(text),
+        Frame::Binary(bytes) => tungstenite::Message::Binary(bytes),
+    }
+}
+
+/// Sends a message to the server. Failures are only reported, as the next
+/// read then reconnects and the pending requests are sent again.
+fn send(socket: &mut Socket, message: &FromPlayer, encoding: Encoding) {
+    if let Err(e) = socket.write_message(to_message(message, encoding)) {
+        println!("Could not send {:?}: {}", message, e);
+    }
+}
+
 fn main() {
-    let (mut socket, _response) = connect(Url::parse("ws://localhost:3001/api/join").unwrap())
+    let usage = "Usage: console-front <room code> <name>";
+    let code = std::env::args().nth(1).expect(usage);
+    let name = std::env::args().nth(2).expect(usage);
+    // Binary frames are smaller, but harder to debug
+    let encoding = if std::env::var("MESSAGEPACK").is_ok_and(|v| v == "1") {
+        Encoding::MessagePack
+    } else {
+        Encoding::Json
+    };
+    let room_url = Url::parse("ws://localhost:3001/api/rooms/")
+        .unwrap()
+        .join(&format!("{}/", code))
+        .unwrap();
+    let mut url = room_url.join("join").unwrap();
+    url.query_pairs_mut().append_pair("name", &name);
+    let mut socket = open(url, encoding)
         .map_err(|e| dbg!(e))
         .expect("Can't connect");
+    let mut token = None;
 
     let stdin = std::io::stdin();
     let mut stdin = stdin.lock().lines();
 
+    let mut state: Option<shared::state::State> = None;
+    let mut ready_sent = false;
+    let mut roster: Vec<shared::PlayerProfile> = vec![];
+
     loop {
-        let msg = socket.read_message().expect("Error reading message");
+        let msg = match socket.read_message() {
+            Ok(msg) => msg,
+            Err(e) => {
+                let token = token.as_deref().expect("Connection lost before joining");
+                println!("Connection lost ({}), reconnecting", e);
+                std::thread::sleep(std::time::Duration::from_secs(1));
+                let mut url = room_url.join("reconnect").unwrap();
+                url.query_pairs_mut().append_pair("token", token);
+                if let Ok(s) = open(url, encoding) {
+                    socket = s;
+                }
+                continue;
+            }
+        };
+        let frame = match msg {
+            tungstenite::Message::Text(text) => Frame::Text(text),
+            tungstenite::Message::Binary(bytes) => Frame::Binary(bytes),
+            // Pings are answered by tungstenite, and closing is handled as
+            // a connection loss on the next read
+            tungstenite::Message::Ping(_)
+            | tungstenite::Message::Pong(_)
+            | tungstenite::Message::Close(_) => continue,
+            tungstenite::Message::Frame(_) => unreachable!(),
+        };
+        let msg: shared::ToPlayer = match frame.decode() {
+            Ok(msg) => msg,
+            Err(e) => {
+                println!("Ignoring invalid message ({}): {:?}", e, frame);
+                continue;
+            }
+        };
         match msg {
-            tungstenite::Message::Text(msg) => {
-                let msg: shared::ToPlayer = serde_json::from_str(&msg).unwrap();
-                match msg {
-                    shared::ToPlayer::ActionRequest { choices } => {
-                        let choice = loop {
-                            println!("Action request for player");
-                            for (i, c) in choices.iter().enumerate() {
-                                print!("  {}: ", i,);
-                                match c {
-                                    shared::Action::Location(l) => {
-                                        println!("{}", shared::state::Locations::from_id(*l))
-                                    }
-                                    shared::Action::DamagePlayer(p, damage) => {
-                                        println!("Damage {:?} for {:?}", p, damage)
-                                    }
-                                    shared::Action::HealPlayer(p, hp) => {
-                                        println!("Heal player {:?} for {:?}", p, hp)
-                                    }
-                                    shared::Action::Skip => {
-                                        println!("Do nothing")
-                                    }
-                                    shared::Action::DiceRoll(dices) => match dices {
-                                        shared::Dices::D4 => println!("Roll D4"),
-                                        shared::Dices::D6 => println!("Roll D6"),
-                                        shared::Dices::Both => println!("Roll both dice"),
-                                    },
+            shared::ToPlayer::ActionRequest {
+                id,
+                prompt,
+                choices,
+            } => {
+                let choice = loop {
+                    println!("{}", describe_prompt(&prompt, state.as_ref()));
+                    for (i, c) in choices.iter().enumerate() {
+                        print!("  {}: ", i,);
+                        match c {
+                            shared::Action::Location(l) => match &state {
+                                Some(state) => {
+                                    println!("{}", state.locations().from_id(*l).name())
                                 }
+                                None => println!("{:?}", l),
+                            },
+                            shared::Action::DamagePlayer(p, damage) => {
+                                println!("Damage {:?} for {:?}", p, damage)
                             }
-                            let input = stdin.next().unwrap().unwrap();
-                            if let Ok(choice) = input.parse() {
-                                if choice < choices.len() {
-                                    break choice;
-                                }
-                                println!("Invalid input");
+                            shared::Action::HealPlayer(p, hp) => {
+                                println!("Heal player {:?} for {:?}", p, hp)
+                            }
+                            shared::Action::Reveal => println!("Reveal your character"),
+                            shared::Action::UseAbility => println!("Use your ability"),
+                            shared::Action::ChoosePlayer(p) => println!("{:?}", p),
+                            shared::Action::HermitAnswer(true) => {
+                                println!("The card applies to me")
                             }
-                        };
-                        socket
-                            .write_message(tungstenite::Message::Text(
-                                serde_json::to_string(&FromPlayer::ActionChoice(choice)).unwrap(),
-                            ))
-                            .unwrap();
+                            shared::Action::HermitAnswer(false) => {
+                                println!("The card does not apply to me")
+                            }
+                            shared::Action::Equipment(e) => println!("Take {}", e.name()),
+                            shared::Action::Skip => {
+                                println!("Do nothing")
+                            }
+                            shared::Action::DiceRoll(dices) => match dices {
+                                shared::Dices::D4 => println!("Roll D4"),
+                                shared::Dices::D6 => println!("Roll D6"),
+                                shared::Dices::Both => println!("Roll both dice"),
+                            },
+                        }
+                    }
+                    println!("  (type \"sync\" to resynchronize the game state)");
+                    println!("  (type \"say <message>\" to talk to the table, or");
+                    println!("   \"tell <player number> <message>\" to a single player)");
+                    println!("  (type \"leave\" to let a bot play for you)");
+                    let input = stdin.next().unwrap().unwrap();
+                    if input.trim() == "sync" {
+                        send(&mut socket, &FromPlayer::RequestStateSync, encoding);
+                        continue;
+                    }
+                    if input.trim() == "leave" {
+                        send(&mut socket, &FromPlayer::Leave, encoding);
+                        socket.close(None).ok();
+                        return;
+                    }
+                    if let Some(chat) = parse_chat(&input) {
+                        match chat {
+                            Some(chat) => send(&mut socket, &chat, encoding),
+                            None => println!("Usage: tell <player number> <message>"),
+                        }
+                        continue;
                     }
-                    msg => println!("Received: {:?}", msg),
+                    if let Ok(choice) = input.parse() {
+                        if choice < choices.len() {
+                            break choice;
+                        }
+                        println!("Invalid input");
+                    }
+                };
+                let choice = FromPlayer::ActionChoice {
+                    request: id,
+                    choice,
+                };
+                send(&mut socket, &choice, encoding);
+            }
+            shared::ToPlayer::StateSync(s) => {
+                println!("Received game state: {:?}", s);
+                println!("Packs in play: {:?}", s.packs());
+                state = Some(*s);
+            }
+            shared::ToPlayer::StateMutation(mutation) => {
+                println!("Received: {:?}", mutation);
+                if let Some(state) = &mut state {
+                    state.mutate(mutation);
                 }
             }
-            tungstenite::Message::Binary(_) => todo!(),
-            tungstenite::Message::Ping(_) => todo!(),
-            tungstenite::Message::Pong(_) => todo!(),
-            tungstenite::Message::Close(_) => todo!(),
-            tungstenite::Message::Frame(_) => unreachable!(),
+            shared::ToPlayer::SessionToken(t) => token = Some(t),
+            shared::ToPlayer::WaitingFor(players) => println!("Waiting for {:?}", players),
+            shared::ToPlayer::Roster { players, you } => {
+                println!("Players in the room:");
+                for (i, p) in players.iter().enumerate() {
+                    let marker = if p.id == you {
+                        " (you)"
+                    } else if p.bot {
+                        " (bot)"
+                    } else {
+                        ""
+                    };
+                    let ready = if p.ready { "ready" } else { "not ready" };
+                    println!("  {}. {}{}: {}", i, p.name, marker, ready);
+                }
+                roster = players;
+                if !ready_sent {
+                    println!("Press enter when ready");
+                    stdin.next().unwrap().unwrap();
+                    send(&mut socket, &FromPlayer::Ready(true), encoding);
+                    ready_sent = true;
+                }
+            }
+            shared::ToPlayer::Chat {
+                from,
+                channel,
+                text,
+            } => {
+                let channel = match channel {
+                    shared::ChatChannel::Table => "table".to_owned(),
+                    shared::ChatChannel::Private(to) => format!("to {}", player_name(&roster, to)),
+                };
+                println!("[{}] {}: {}", channel, player_name(&roster, from), text);
+            }
+            shared::ToPlayer::Welcome { version, features } => {
+                println!(
+                    "Connected with protocol {}, server features: {:?}",
+                    version, features
+                )
+            }
+            shared::ToPlayer::Error(shared::ErrorMessage::UnsupportedVersion {
+                client,
+                server,
+            }) => {
+                println!(
+                    "The server speaks protocol {} but this client speaks {}, please update",
+                    server, client
+                );
+                break;
+            }
+            shared::ToPlayer::Error(error) => println!("Error: {:?}", error),
+            shared::ToPlayer::Info(shared::InfoMessage::GameOver { winners }) => {
+                println!("Game over, won by {:?}", winners);
+                break;
+            }
+            msg => println!("Received: {:?}", msg),
         }
     }
 }
+
+/// Chat message typed at a prompt, `Some(None)` meaning a malformed one.
+fn parse_chat(input: &str) -> Option<Option<FromPlayer>> {
+    if let Some(text) = input.strip_prefix("say ") {
+        return Some(Some(FromPlayer::Chat {
+            channel: shared::ChatChannel::Table,
+            text: text.to_owned(),
+        }));
+    }
+    let rest = input.strip_prefix("tell ")?;
+    let chat = rest.split_once(' ').and_then(|(to, text)| {
+        Some(FromPlayer::Chat {
+            channel: shared::ChatChannel::Private(shared::PlayerId::new(to.parse().ok()?)),
+            text: text.to_owned(),
+        })
+    });
+    Some(chat)
+}
+
+fn player_name(roster: &[shared::PlayerProfile], id: shared::PlayerId) -> String {
+    match roster.iter().find(|p| p.id == id) {
+        Some(p) => p.name.clone(),
+        None => format!("{:?}", id),
+    }
+}
+
+fn describe_prompt(prompt: &shared::Prompt, state: Option<&shared::state::State>) -> String {
+    let kind = match prompt.kind {
+        shared::PromptKind::MovementRoll => "Roll the dice to move",
+        shared::PromptKind::LocationChoice => "Choose where to move",
+        shared::PromptKind::LocationEffect => "Apply the effect of the location",
+        shared::PromptKind::AttackTarget => "Choose who to attack",
+        shared::PromptKind::AttackRoll => "Roll the dice to attack",
+        shared::PromptKind::EffectRoll => "Roll for the effect",
+        shared::PromptKind::CardTarget => "Choose the target of the card",
+        shared::PromptKind::HermitAnswer => "Answer the Hermit card",
+        shared::PromptKind::RevealWindow => "You may reveal your character",
+        shared::PromptKind::Ability => "Use the ability of your character",
+    };
+    match &prompt.source {
+        Some(shared::PromptSource::Location(l)) => match state {
+            Some(state) => format!("{} ({})", kind, state.locations().from_id(*l).name()),
+            None => format!("{} ({:?})", kind, l),
+        },
+        Some(shared::PromptSource::Character(c)) => format!("{} ({:?})", kind, c),
+        Some(shared::PromptSource::Card(card)) => format!("{} ({})", kind, card),
+        None => kind.to_owned(),
+    }
+}
@@ -0,0 +1,453 @@
+use std::sync::Arc;
+
+use anyhow::Result;
+use shared::{
+    state::{Faction, Pack, Status},
+    Action, Dices, InfoMessage, PlayerId,
+};
+
+use super::{ability_prompt, use_ability, Character};
+use crate::{Event, EventHandler, Flow, GameLogic};
+
+#[derive(Debug)]
+pub(super) struct Gregor;
+impl Character for Gregor {
+    fn name(&self) -> &'static str {
+        "Gregor"
+    }
+
+    fn faction(&self) -> Faction {
+        Faction::Hunter
+    }
+
+    fn hit_point(&self) -> usize {
+        14
+    }
+
+    fn pack(&self) -> Pack {
+        Pack::Expansion
+    }
+
+    fn register_ability(&self, player_id: PlayerId, game_logic: &mut GameLogic) {
+        game_logic.register_handler(0, Arc::new(GhostlyBarrier { player_id }));
+    }
+}
+
+/// Once per game at the start of his turn, Gregor takes no damage until his
+/// next turn.
+struct GhostlyBarrier {
+    player_id: PlayerId,
+}
+
+#[async_trait::async_trait]
+impl EventHandler for GhostlyBarrier {
+    async fn handle(&self, game_logic: &mut GameLogic, event: &mut Event) -> Result<Flow> {
+        if !matches!(*event, Event::TurnStart { player } if player == self.player_id) {
+            return Ok(Flow::Continue);
+        }
+        let Some(prompt) = ability_prompt(game_logic, self.player_id, true) else {
+            return Ok(Flow::Continue);
+        };
+        let used = game_logic
+            .message_channel
+            .request_action_map(
+                self.player_id,
+                prompt,
+                [(Action::UseAbility, true), (Action::Skip, false)],
+            )
+            .await?;
+        if used {
+            use_ability(game_logic, self.player_id).await?;
+            game_logic
+                .apply_status(self.player_id, Status::Immune, 1)
+                .await?;
+            game_logic
+                .broadcast_info(InfoMessage::Basic(format!(
+                    "{} takes no damage until its next turn",
+                    game_logic.name(self.player_id)
+                )))
+                .await?;
+        }
+        Ok(Flow::Continue)
+    }
+}
+
+#[derive(Debug)]
+pub(super) struct Emi;
+impl Character for Emi {
+    fn name(&self) -> &'static str {
+        "Emi"
+    }
+
+    fn faction(&self) -> Faction {
+        Faction::Hunter
+    }
+
+    fn hit_point(&self) -> usize {
+        10
+    }
+
+    fn pack(&self) -> Pack {
+        Pack::Base
+    }
+
+    fn register_ability(&self, player_id: PlayerId, game_logic: &mut GameLogic) {
+        game_logic.register_handler(0, Arc::new(Teleport { player_id }));
+    }
+}
+
+/// When moving, Emi can go to an adjacent location instead of rolling the
+/// dice.
+struct Teleport {
+    player_id: PlayerId,
+}
+
+#[async_trait::async_trait]
+impl EventHandler for Teleport {
+    async fn handle(&self, game_logic: &mut GameLogic, event: &mut Event) -> Result<Flow> {
+        let Event::Movement {
+            player,
+            destination,
+        } = event
+        else {
+            return Ok(Flow::Continue);
+        };
+        if *player != self.player_id || destination.is_some() {
+            return Ok(Flow::Continue);
+        }
+        let Some(prompt) = ability_prompt(game_logic, self.player_id, false) else {
+            return Ok(Flow::Continue);
+        };
+        let Some(location) = game_logic.state.player(self.player_id).location() else {
+            return Ok(Flow::Continue);
+        };
+        let adjacent = game_logic
+            .state
+            .locations()
+            .adjacent(location.id())
+            .map(|l| l.id());
+        *destination = game_logic
+            .message_channel
+            .request_action_map(
+                self.player_id,
+                prompt,
+                std::iter::once((Action::Skip, None))
+                    .chain(adjacent.map(|l| (Action::Location(l), Some(l)))),
+            )
+            .await?;
+        Ok(Flow::Continue)
+    }
+}
+
+#[derive(Debug)]
+pub(super) struct Franklin;
+impl Character for Franklin {
+    fn name(&self) -> &'static str {
+        "Franklin"
+    }
+
+    fn faction(&self) -> Faction {
+        Faction::Hunter
+    }
+
+    fn hit_point(&self) -> usize {
+        12
+    }
+
+    fn pack(&self) -> Pack {
+        Pack::Base
+    }
+
+    fn register_ability(&self, player_id: PlayerId, game_logic: &mut GameLogic) {
+        game_logic.register_handler(
+            0,
+            Arc::new(DiceDamage {
+                player_id,
+                dices: Dices::D6,
+            }),
+        );
+    }
+}
+
+#[derive(Debug)]
+pub(super) struct George;
+impl Character for George {
+    fn name(&self) -> &'static str {
+        "George"
+    }
+
+    fn faction(&self) -> Faction {
+        Faction::Hunter
+    }
+
+    fn hit_point(&self) -> usize {
+        14
+    }
+
+    fn pack(&self) -> Pack {
+        Pack::Base
+    }
+
+    fn register_ability(&self, player_id: PlayerId, game_logic: &mut GameLogic) {
+        game_logic.register_handler(
+            0,
+            Arc::new(DiceDamage {
+                player_id,
+                dices: Dices::D4,
+            }),
+        );
+    }
+}
+
+/// Once per game at the start of their turn, Franklin and George deal as
+/// much damage as a die roll to the player of their choice.
+struct DiceDamage {
+    player_id: PlayerId,
+    dices: Dices,
+}
+
+#[async_trait::async_trait]
+impl EventHandler for DiceDamage {
+    async fn handle(&self, game_logic: &mut GameLogic, event: &mut Event) -> Result<Flow> {
+        if !matches!(*event, Event::TurnStart { player } if player == self.player_id) {
+            return Ok(Flow::Continue);
+        }
+        let Some(prompt) = ability_prompt(game_logic, self.player_id, true) else {
+            return Ok(Flow::Continue);
+        };
+        let targets = game_logic
+            .state
+            .players()
+            .filter(|p| p.is_alive() && p.id() != self.player_id)
+            .map(|p| (Action::DamagePlayer(p.id(), None), Some(p.id())))
+            .collect::<Vec<_>>();
+        let Some(target) = game_logic
+            .message_channel
+            .request_action_map(
+                self.player_id,
+                prompt.clone(),
+                targets.into_iter().chain([(Action::Skip, None)]),
+            )
+            .await?
+        else {
+            return Ok(Flow::Continue);
+        };
+        use_ability(game_logic, self.player_id).await?;
+        let roll = game_logic
+            .request_roll(self.player_id, prompt, &[self.dices])
+            .await?;
+        game_logic
+            .damage(Some(self.player_id), target, roll.sum())
+            .await?;
+        Ok(Flow::Continue)
+    }
+}
+
+#[derive(Debug)]
+pub(super) struct FuKa;
+impl Character for FuKa {
+    fn name(&self) -> &'static str {
+        "Fu-ka"
+    }
+
+    fn faction(&self) -> Faction {
+        Faction::Hunter
+    }
+
+    fn hit_point(&self) -> usize {
+        12
+    }
+
+    fn pack(&self) -> Pack {
+        Pack::Expansion
+    }
+
+    fn register_ability(&self, player_id: PlayerId, game_logic: &mut GameLogic) {
+        game_logic.register_handler(0, Arc::new(BrutalCare { player_id }));
+    }
+}
+
+/// Once per game at the start of her turn, Fu-ka sets the damage of a player
+/// of her choice to 7.
+struct BrutalCare {
+    player_id: PlayerId,
+}
+
+/// Damage Fu-ka sets her target to.
+const BRUTAL_CARE_DAMAGE: usize = 7;
+
+#[async_trait::async_trait]
+impl EventHandler for BrutalCare {
+    async fn handle(&self, game_logic: &mut GameLogic, event: &mut Event) -> Result<Flow> {
+        if !matches!(*event, Event::TurnStart { player } if player == self.player_id) {
+            return Ok(Flow::Continue);
+        }
+        let Some(prompt) = ability_prompt(game_logic, self.player_id, true) else {
+            return Ok(Flow::Continue);
+        };
+        let targets = game_logic
+            .state
+            .players()
+            .filter(|p| p.is_alive())
+            .map(|p| (Action::ChoosePlayer(p.id()), Some(p.id())))
+            .collect::<Vec<_>>();
+        let Some(target) = game_logic
+            .message_channel
+            .request_action_map(
+                self.player_id,
+                prompt,
+                targets.into_iter().chain([(Action::Skip, None)]),
+            )
+            .await?
+        else {
+            return Ok(Flow::Continue);
+        };
+        use_ability(game_logic, self.player_id).await?;
+        let damage = game_logic.state.player(target).damage();
+        if damage < BRUTAL_CARE_DAMAGE {
+            game_logic
+                .damage(Some(self.player_id), target, BRUTAL_CARE_DAMAGE - damage)
+                .await?;
+        } else {
+            game_logic
+                .heal(Some(self.player_id), target, damage - BRUTAL_CARE_DAMAGE)
+                .await?;
+        }
+        Ok(Flow::Continue)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use shared::{
+        state::{Mutation, Status},
+        Action, PlayerId,
+    };
+
+    use crate::{
+        game_logic::test_utils::{choose, game_with, reveal},
+        Event,
+    };
+
+    #[tokio::test]
+    async fn gregor_shield_lasts_until_next_turn() {
+        let mut game_logic = game_with(&["Gregor", "Franklin"], |_, _, choices| {
+            choose(choices, Action::UseAbility)
+        });
+        let gregor = PlayerId::new(0);
+        let turn_start = Event::TurnStart { player: gregor };
+
+        // Hidden characters cannot use their ability
+        game_logic.dispatch(turn_start.clone()).await.unwrap();
+        assert!(!game_logic.state.player(gregor).ability_used());
+
+        reveal(&mut game_logic, gregor).await;
+        game_logic.dispatch(turn_start.clone()).await.unwrap();
+        assert!(game_logic.state.player(gregor).ability_used());
+        game_logic
+            .damage(Some(PlayerId::new(1)), gregor, 5)
+            .await
+            .unwrap();
+        assert_eq!(game_logic.state.player(gregor).damage(), 0);
+
+        // Franklin's turn, then back to Gregor
+        game_logic.next_player().await.unwrap();
+        assert!(game_logic.state.player(gregor).has_status(Status::Immune));
+        game_logic.next_player().await.unwrap();
+        game_logic.dispatch(turn_start).await.unwrap();
+        game_logic
+            .damage(Some(PlayerId::new(1)), gregor, 5)
+            .await
+            .unwrap();
+        assert_eq!(game_logic.state.player(gregor).damage(), 5);
+    }
+
+    #[tokio::test]
+    async fn emi_teleports_to_adjacent_location() {
+        // Adjacent locations are offered last
+        let mut game_logic = game_with(&["Emi", "Gregor"], |_, _, choices| choices.len() - 1);
+        let emi = PlayerId::new(0);
+        let location = game_logic.state.locations().iter().next().unwrap().id();
+        let adjacent = game_logic.state.locations().adjacent(location);
+        let adjacent = adjacent.last().unwrap().id();
+        game_logic
+            .mutate_state(Mutation::Move(emi, location))
+            .await
+            .unwrap();
+
+        let movement = Event::Movement {
+            player: emi,
+            destination: None,
+        };
+        assert_eq!(
+            game_logic.dispatch(movement.clone()).await.unwrap(),
+            Some(movement.clone())
+        );
+
+        reveal(&mut game_logic, emi).await;
+        assert_eq!(
+            game_logic.dispatch(movement).await.unwrap(),
+            Some(Event::Movement {
+                player: emi,
+                destination: Some(adjacent)
+            })
+        );
+    }
+
+    #[tokio::test]
+    async fn franklin_and_george_deal_dice_damage_once() {
+        let (franklin, george, target) = (PlayerId::new(0), PlayerId::new(1), PlayerId::new(2));
+        let mut game_logic = game_with(&["Franklin", "George", "Gregor"], move |_, _, choices| {
+            choose(choices, Action::DamagePlayer(target, None))
+        });
+        reveal(&mut game_logic, franklin).await;
+        reveal(&mut game_logic, george).await;
+
+        game_logic
+            .dispatch(Event::TurnStart { player: franklin })
+            .await
+            .unwrap();
+        let damage = game_logic.state.player(target).damage();
+        assert!((1..=6).contains(&damage));
+
+        game_logic
+            .dispatch(Event::TurnStart { player: george })
+            .await
+            .unwrap();
+        let george_damage = game_logic.state.player(target).damage() - damage;
+        assert!((1..=4).contains(&george_damage));
+
+        for player in [franklin, george] {
+            assert!(game_logic.state.player(player).ability_used());
+            game_logic
+                .dispatch(Event::TurnStart { player })
+                .await
+                .unwrap();
+        }
+        assert_eq!(
+            game_logic.state.player(target).damage(),
+            damage + george_damage
+        );
+    }
+
+    #[tokio::test]
+    async fn fu_ka_sets_damage_to_seven_once() {
+        let [fu_ka, liche, vampire] = [0, 1, 2].map(PlayerId::new);
+        let mut targets = vec![vampire, liche];
+        let mut game_logic = game_with(&["Fu-ka", "Liche", "Vampire"], move |_, _, choices| {
+            choose(choices, Action::ChoosePlayer(targets.pop().unwrap()))
+        });
+        reveal(&mut game_logic, fu_ka).await;
+        let turn_start = Event::TurnStart { player: fu_ka };
+        game_logic.damage(None, liche, 2).await.unwrap();
+        game_logic.damage(None, vampire, 9).await.unwrap();
+
+        game_logic.dispatch(turn_start.clone()).await.unwrap();
+        assert!(game_logic.state.player(fu_ka).ability_used());
+        assert_eq!(game_logic.state.player(liche).damage(), 7);
+
+        // Once per game
+        game_logic.dispatch(turn_start).await.unwrap();
+        assert_eq!(game_logic.state.player(vampire).damage(), 9);
+    }
+}
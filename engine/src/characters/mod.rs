@@ -1,18 +1,69 @@
-#[derive(Debug, Clone, Copy, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
-pub struct CharacterId(usize);
+mod hunters;
+mod neutrals;
+mod shadows;
+
+use anyhow::{ensure, Result};
+use shared::{
+    state::{Faction, Mutation, Pack, State},
+    CharacterId, PlayerId, Prompt, PromptKind, PromptSource,
+};
+
+use crate::GameLogic;
 
 pub trait Character: core::fmt::Debug + Send + Sync {
+    fn name(&self) -> &'static str;
+    fn faction(&self) -> Faction;
     fn hit_point(&self) -> usize;
+    fn pack(&self) -> Pack;
+
+    /// Whether the character may give a false answer to Hermit cards.
+    fn can_lie(&self) -> bool {
+        false
+    }
+
+    /// Registers the event handlers implementing the ability of the
+    /// character, for the player playing it.
+    fn register_ability(&self, _player_id: PlayerId, _game_logic: &mut GameLogic) {}
+
+    /// Whether the player playing the character won. Called after every
+    /// change to the game with `game_over` unset, the game ending as soon as
+    /// a player won, then once more with `game_over` set to find out who
+    /// wins alongside.
+    ///
+    /// Hunters win once every Shadow is dead, Shadows once every Hunter or
+    /// three Neutrals are dead. Neutrals have their own conditions.
+    fn has_won(&self, _player_id: PlayerId, game_logic: &GameLogic, _game_over: bool) -> bool {
+        match self.faction() {
+            Faction::Hunter => faction_wiped_out(&game_logic.state, Faction::Shadow),
+            Faction::Shadow => {
+                faction_wiped_out(&game_logic.state, Faction::Hunter)
+                    || dead_players(&game_logic.state, Faction::Neutral) >= 3
+            }
+            Faction::Neutral => false,
+        }
+    }
 }
 
 lazy_static::lazy_static! {
     static ref CHARACTERS: Vec<&'static dyn Character> = {
         vec![
-            &Gregor,
-            &Metamorphe,
-            &Allie,
-            &Bob,
-            &Liche,
+            &hunters::Gregor,
+            &shadows::Metamorphe,
+            &neutrals::Allie,
+            &neutrals::Bob,
+            &shadows::Liche,
+            &hunters::Emi,
+            &hunters::Franklin,
+            &hunters::George,
+            &shadows::Vampire,
+            &shadows::Werewolf,
+            &neutrals::Charles,
+            &hunters::FuKa,
+            &shadows::UltraSoul,
+            &neutrals::Catherine,
+            &neutrals::Agnes,
+            &neutrals::Bryan,
+            &neutrals::David,
         ]
     };
 }
@@ -22,56 +73,142 @@ pub struct Characters;
 
 impl Characters {
     pub fn from_id(id: CharacterId) -> &'static dyn Character {
-        CHARACTERS[id.0]
+        CHARACTERS[usize::from(id)]
     }
 
-    #[cfg(feature = "game-logic")]
-    pub fn generate(player_count: usize) -> Vec<CharacterId> {
-        use rand::seq::IteratorRandom;
-        assert!(player_count <= CHARACTERS.len());
-        (0..CHARACTERS.len())
-            .into_iter()
-            .map(CharacterId)
-            .choose_multiple(&mut rand::thread_rng(), player_count)
+    /// Characters as seen by clients, indexed by `CharacterId`.
+    pub fn states() -> Vec<shared::state::Character> {
+        CHARACTERS
+            .iter()
+            .enumerate()
+            .map(|(i, c)| {
+                shared::state::Character::new(
+                    CharacterId::new(i),
+                    c.name().to_owned(),
+                    c.faction(),
+                    c.hit_point(),
+                    c.pack(),
+                )
+            })
+            .collect()
     }
-}
 
-#[derive(Debug)]
-struct Gregor;
-impl Character for Gregor {
-    fn hit_point(&self) -> usize {
-        14
+    /// Deals characters from the given packs, with as many Hunters as
+    /// Shadows and Neutrals for the remaining players.
+    pub fn generate(player_count: usize, packs: &[Pack]) -> Result<Vec<CharacterId>> {
+        use rand::seq::{IteratorRandom, SliceRandom};
+        let sides = match player_count {
+            0..=3 => player_count / 2,
+            4..=7 => 2,
+            _ => (player_count - 2) / 2,
+        };
+        let neutrals = player_count - 2 * sides;
+        let mut characters = vec![];
+        for (faction, count) in [
+            (Faction::Hunter, sides),
+            (Faction::Shadow, sides),
+            (Faction::Neutral, neutrals),
+        ] {
+            let dealt = (0..CHARACTERS.len())
+                .filter(|&i| {
+                    CHARACTERS[i].faction() == faction && packs.contains(&CHARACTERS[i].pack())
+                })
+                .map(CharacterId::new)
+                .choose_multiple(&mut rand::thread_rng(), count);
+            ensure!(
+                dealt.len() == count,
+                "Not enough {:?} characters in {:?} for {} players",
+                faction,
+                packs,
+                player_count
+            );
+            characters.extend(dealt);
+        }
+        characters.shuffle(&mut rand::thread_rng());
+        Ok(characters)
     }
 }
 
-#[derive(Debug)]
-struct Metamorphe;
-impl Character for Metamorphe {
-    fn hit_point(&self) -> usize {
-        11
+/// Prompt asking a player how to use the ability of its character, or
+/// `None` if the ability cannot be used: the character must be alive and
+/// revealed, and once per game abilities must not have been used yet.
+pub(crate) fn ability_prompt(
+    game_logic: &GameLogic,
+    player_id: PlayerId,
+    once_per_game: bool,
+) -> Option<Prompt> {
+    let player = game_logic.state.player(player_id);
+    if !player.is_alive() || !player.revealed() || (once_per_game && player.ability_used()) {
+        return None;
     }
+    let character = player.character()?.id();
+    Some(Prompt::new(PromptKind::Ability).with_source(PromptSource::Character(character)))
 }
 
-#[derive(Debug)]
-struct Allie;
-impl Character for Allie {
-    fn hit_point(&self) -> usize {
-        8
-    }
+/// Records that a player used its once per game ability.
+pub(crate) async fn use_ability(game_logic: &mut GameLogic, player_id: PlayerId) -> Result<()> {
+    game_logic
+        .mutate_state(Mutation::UseAbility(player_id))
+        .await
 }
 
-#[derive(Debug)]
-struct Bob;
-impl Character for Bob {
-    fn hit_point(&self) -> usize {
-        13
-    }
+fn faction_players(
+    state: &State,
+    faction: Faction,
+) -> impl Iterator<Item = shared::state::Player<'_>> {
+    state.players().filter(move |p| {
+        p.character()
+            .expect("The engine knows every character")
+            .faction()
+            == faction
+    })
 }
 
-#[derive(Debug)]
-struct Liche;
-impl Character for Liche {
-    fn hit_point(&self) -> usize {
-        14
+/// Whether every player of a faction is dead, provided it had players.
+fn faction_wiped_out(state: &State, faction: Faction) -> bool {
+    let mut players = faction_players(state, faction).peekable();
+    players.peek().is_some() && players.all(|p| !p.is_alive())
+}
+
+fn dead_players(state: &State, faction: Faction) -> usize {
+    faction_players(state, faction)
+        .filter(|p| !p.is_alive())
+        .count()
+}
+
+#[cfg(test)]
+mod tests {
+    use shared::state::{Faction, Pack};
+
+    use super::Characters;
+
+    #[test]
+    fn generate_keeps_factions_balanced() {
+        let count = |characters: &[_], faction| {
+            characters
+                .iter()
+                .filter(|&&c| Characters::from_id(c).faction() == faction)
+                .count()
+        };
+        for (players, sides, neutrals) in [(4, 2, 0), (5, 2, 1), (7, 2, 3), (8, 3, 2)] {
+            let characters = Characters::generate(players, &[Pack::Base]).unwrap();
+            assert_eq!(count(&characters, Faction::Hunter), sides);
+            assert_eq!(count(&characters, Faction::Shadow), sides);
+            assert_eq!(count(&characters, Faction::Neutral), neutrals);
+            assert!(characters
+                .iter()
+                .all(|&c| Characters::from_id(c).pack() == Pack::Base));
+        }
+
+        // The base pack only has three Hunters
+        assert!(Characters::generate(10, &[Pack::Base]).is_err());
+        assert!(Characters::generate(10, &[Pack::Base, Pack::Expansion]).is_ok());
+
+        // The expansion pack can be played alone
+        let characters = Characters::generate(7, &[Pack::Expansion]).unwrap();
+        assert_eq!(count(&characters, Faction::Neutral), 3);
+        assert!(characters
+            .iter()
+            .all(|&c| Characters::from_id(c).pack() == Pack::Expansion));
     }
 }
@@ -0,0 +1,695 @@
+use std::sync::Arc;
+
+use anyhow::Result;
+use shared::{
+    state::{Faction, Mutation, Pack},
+    Action, InfoMessage, PlayerId,
+};
+
+use super::{ability_prompt, use_ability, Character, Characters};
+use crate::{Event, EventHandler, Flow, GameLogic, LocationEffect};
+
+/// Equipment Bob needs to win.
+const BOB_EQUIPMENT: usize = 5;
+
+#[derive(Debug)]
+pub(super) struct Allie;
+impl Character for Allie {
+    fn name(&self) -> &'static str {
+        "Allie"
+    }
+
+    fn faction(&self) -> Faction {
+        Faction::Neutral
+    }
+
+    fn hit_point(&self) -> usize {
+        8
+    }
+
+    fn pack(&self) -> Pack {
+        Pack::Base
+    }
+
+    fn register_ability(&self, player_id: PlayerId, game_logic: &mut GameLogic) {
+        game_logic.register_handler(0, Arc::new(MothersLove { player_id }));
+    }
+
+    /// Allie wins if she is alive when the game is over.
+    fn has_won(&self, player_id: PlayerId, game_logic: &GameLogic, game_over: bool) -> bool {
+        game_over && game_logic.state.player(player_id).is_alive()
+    }
+}
+
+/// Once per game at the start of her turn, Allie heals all her damage.
+struct MothersLove {
+    player_id: PlayerId,
+}
+
+#[async_trait::async_trait]
+impl EventHandler for MothersLove {
+    async fn handle(&self, game_logic: &mut GameLogic, event: &mut Event) -> Result<Flow> {
+        if !matches!(*event, Event::TurnStart { player } if player == self.player_id) {
+            return Ok(Flow::Continue);
+        }
+        let damage = game_logic.state.player(self.player_id).damage();
+        if damage == 0 {
+            return Ok(Flow::Continue);
+        }
+        let Some(prompt) = ability_prompt(game_logic, self.player_id, true) else {
+            return Ok(Flow::Continue);
+        };
+        let used = game_logic
+            .message_channel
+            .request_action_map(
+                self.player_id,
+                prompt,
+                [
+                    (Action::HealPlayer(self.player_id, Some(damage)), true),
+                    (Action::Skip, false),
+                ],
+            )
+            .await?;
+        if used {
+            use_ability(game_logic, self.player_id).await?;
+            game_logic
+                .heal(Some(self.player_id), self.player_id, damage)
+                .await?;
+        }
+        Ok(Flow::Continue)
+    }
+}
+
+#[derive(Debug)]
+pub(super) struct Bob;
+impl Character for Bob {
+    fn name(&self) -> &'static str {
+        "Bob"
+    }
+
+    fn faction(&self) -> Faction {
+        Faction::Neutral
+    }
+
+    fn hit_point(&self) -> usize {
+        13
+    }
+
+    fn pack(&self) -> Pack {
+        Pack::Base
+    }
+
+    fn register_ability(&self, player_id: PlayerId, game_logic: &mut GameLogic) {
+        game_logic.register_handler(0, Arc::new(Robbery { player_id }));
+    }
+
+    /// Bob wins as soon as he has enough equipment.
+    fn has_won(&self, player_id: PlayerId, game_logic: &GameLogic, _game_over: bool) -> bool {
+        game_logic.state.player(player_id).equipment().len() >= BOB_EQUIPMENT
+    }
+}
+
+/// When his attack would deal 2 damage or more, Bob can take an equipment
+/// from his target instead.
+struct Robbery {
+    player_id: PlayerId,
+}
+
+#[async_trait::async_trait]
+impl EventHandler for Robbery {
+    async fn handle(&self, game_logic: &mut GameLogic, event: &mut Event) -> Result<Flow> {
+        let Event::BeforeAttack {
+            attacker,
+            target,
+            damage,
+        } = *event
+        else {
+            return Ok(Flow::Continue);
+        };
+        if attacker != self.player_id || damage < 2 {
+            return Ok(Flow::Continue);
+        }
+        let Some(prompt) = ability_prompt(game_logic, self.player_id, false) else {
+            return Ok(Flow::Continue);
+        };
+        let equipment = game_logic.state.player(target).equipment().to_vec();
+        if equipment.is_empty() {
+            return Ok(Flow::Continue);
+        }
+        let Some(equipment) = game_logic
+            .message_channel
+            .request_action_map(
+                self.player_id,
+                prompt,
+                std::iter::once((Action::Skip, None)).chain(
+                    equipment
+                        .into_iter()
+                        .map(|e| (Action::Equipment(e), Some(e))),
+                ),
+            )
+            .await?
+        else {
+            return Ok(Flow::Continue);
+        };
+        game_logic
+            .mutate_state(Mutation::TransferEquipment(
+                equipment,
+                target,
+                self.player_id,
+            ))
+            .await?;
+        game_logic
+            .broadcast_info(InfoMessage::Basic(format!(
+                "{} takes {} from {} instead of dealing damage",
+                game_logic.name(self.player_id),
+                equipment.name(),
+                game_logic.name(target)
+            )))
+            .await?;
+        Ok(Flow::Cancel)
+    }
+}
+
+#[derive(Debug)]
+pub(super) struct Charles;
+impl Character for Charles {
+    fn name(&self) -> &'static str {
+        "Charles"
+    }
+
+    fn faction(&self) -> Faction {
+        Faction::Neutral
+    }
+
+    fn hit_point(&self) -> usize {
+        11
+    }
+
+    fn pack(&self) -> Pack {
+        Pack::Base
+    }
+
+    fn register_ability(&self, player_id: PlayerId, game_logic: &mut GameLogic) {
+        game_logic.register_handler(0, Arc::new(BloodyFeast { player_id }));
+    }
+
+    /// Charles wins once he killed a player and at least three players are
+    /// dead.
+    fn has_won(&self, player_id: PlayerId, game_logic: &GameLogic, _game_over: bool) -> bool {
+        let dead = game_logic.state.players().filter(|p| !p.is_alive());
+        dead.clone().any(|p| p.killer() == Some(player_id)) && dead.count() >= 3
+    }
+}
+
+/// After attacking, Charles can take 2 damage to attack the same player
+/// again.
+struct BloodyFeast {
+    player_id: PlayerId,
+}
+
+#[async_trait::async_trait]
+impl EventHandler for BloodyFeast {
+    async fn handle(&self, game_logic: &mut GameLogic, event: &mut Event) -> Result<Flow> {
+        let Event::AfterAttack {
+            attacker, target, ..
+        } = *event
+        else {
+            return Ok(Flow::Continue);
+        };
+        if attacker != self.player_id || !game_logic.state.player(target).is_alive() {
+            return Ok(Flow::Continue);
+        }
+        let Some(prompt) = ability_prompt(game_logic, self.player_id, false) else {
+            return Ok(Flow::Continue);
+        };
+        let attack_again = game_logic
+            .message_channel
+            .request_action_map(
+                self.player_id,
+                prompt,
+                [
+                    (Action::DamagePlayer(target, None), true),
+                    (Action::Skip, false),
+                ],
+            )
+            .await?;
+        if !attack_again {
+            return Ok(Flow::Continue);
+        }
+        game_logic
+            .damage(Some(self.player_id), self.player_id, 2)
+            .await?;
+        if game_logic.state.player(self.player_id).is_alive() {
+            game_logic.attack_player(self.player_id, target).await?;
+        }
+        Ok(Flow::Continue)
+    }
+}
+
+#[derive(Debug)]
+pub(super) struct Catherine;
+impl Character for Catherine {
+    fn name(&self) -> &'static str {
+        "Catherine"
+    }
+
+    fn faction(&self) -> Faction {
+        Faction::Neutral
+    }
+
+    fn hit_point(&self) -> usize {
+        11
+    }
+
+    fn pack(&self) -> Pack {
+        Pack::Expansion
+    }
+
+    fn register_ability(&self, player_id: PlayerId, game_logic: &mut GameLogic) {
+        game_logic.register_handler(0, Arc::new(Stigmata { player_id }));
+    }
+
+    /// Catherine wins if she is the first to die, or one of the last two
+    /// players alive.
+    fn has_won(&self, player_id: PlayerId, game_logic: &GameLogic, _game_over: bool) -> bool {
+        let alive = game_logic.state.players().filter(|p| p.is_alive()).count();
+        if game_logic.state.player(player_id).is_alive() {
+            alive <= 2
+        } else {
+            alive == game_logic.state.players().len() - 1
+        }
+    }
+}
+
+/// At the start of her turn, Catherine heals 1 damage.
+struct Stigmata {
+    player_id: PlayerId,
+}
+
+#[async_trait::async_trait]
+impl EventHandler for Stigmata {
+    async fn handle(&self, game_logic: &mut GameLogic, event: &mut Event) -> Result<Flow> {
+        if !matches!(*event, Event::TurnStart { player } if player == self.player_id) {
+            return Ok(Flow::Continue);
+        }
+        let player = game_logic.state.player(self.player_id);
+        if !player.revealed() || player.damage() == 0 {
+            return Ok(Flow::Continue);
+        }
+        game_logic
+            .heal(Some(self.player_id), self.player_id, 1)
+            .await?;
+        Ok(Flow::Continue)
+    }
+}
+
+#[derive(Debug)]
+pub(super) struct Agnes;
+impl Character for Agnes {
+    fn name(&self) -> &'static str {
+        "Agnes"
+    }
+
+    fn faction(&self) -> Faction {
+        Faction::Neutral
+    }
+
+    fn hit_point(&self) -> usize {
+        8
+    }
+
+    fn pack(&self) -> Pack {
+        Pack::Expansion
+    }
+
+    /// Agnes wins if the player playing just before her wins.
+    fn has_won(&self, player_id: PlayerId, game_logic: &GameLogic, game_over: bool) -> bool {
+        let players = game_logic.state.players().collect::<Vec<_>>();
+        let position = players
+            .iter()
+            .position(|p| p.id() == player_id)
+            .expect("Agnes plays in the game");
+        let previous = &players[(position + players.len() - 1) % players.len()];
+        let character = previous
+            .character()
+            .expect("The engine knows every character")
+            .id();
+        Characters::from_id(character).has_won(previous.id(), game_logic, game_over)
+    }
+}
+
+/// Hit points from which a character killed by Bryan makes him win.
+const BRYAN_PREY_HIT_POINTS: usize = 13;
+
+#[derive(Debug)]
+pub(super) struct Bryan;
+impl Character for Bryan {
+    fn name(&self) -> &'static str {
+        "Bryan"
+    }
+
+    fn faction(&self) -> Faction {
+        Faction::Neutral
+    }
+
+    fn hit_point(&self) -> usize {
+        10
+    }
+
+    fn pack(&self) -> Pack {
+        Pack::Expansion
+    }
+
+    fn register_ability(&self, player_id: PlayerId, game_logic: &mut GameLogic) {
+        game_logic.register_handler(0, Arc::new(MyGod { player_id }));
+    }
+
+    /// Bryan wins once he killed a character with 13 hit points or more, or
+    /// if he is in the Erstwhile Altar when the game is over.
+    fn has_won(&self, player_id: PlayerId, game_logic: &GameLogic, game_over: bool) -> bool {
+        let killed_prey = game_logic.state.players().any(|p| {
+            p.killer() == Some(player_id)
+                && p.character()
+                    .is_some_and(|c| c.hit_points() >= BRYAN_PREY_HIT_POINTS)
+        });
+        let player = game_logic.state.player(player_id);
+        killed_prey
+            || (game_over
+                && player.is_alive()
+                && player.location().is_some_and(|l| {
+                    *game_logic.location_effect(l.id()) == LocationEffect::StealEquipment
+                }))
+    }
+}
+
+/// Bryan must reveal himself when he kills a character with less than 13
+/// hit points.
+struct MyGod {
+    player_id: PlayerId,
+}
+
+#[async_trait::async_trait]
+impl EventHandler for MyGod {
+    async fn handle(&self, game_logic: &mut GameLogic, event: &mut Event) -> Result<Flow> {
+        let Event::Death {
+            player,
+            killer: Some(killer),
+        } = *event
+        else {
+            return Ok(Flow::Continue);
+        };
+        let bryan = game_logic.state.player(self.player_id);
+        let prey = game_logic.state.player(player);
+        if killer != self.player_id
+            || bryan.revealed()
+            || prey
+                .character()
+                .is_some_and(|c| c.hit_points() >= BRYAN_PREY_HIT_POINTS)
+        {
+            return Ok(Flow::Continue);
+        }
+        let character = bryan
+            .character()
+            .expect("The engine knows every character")
+            .id();
+        game_logic
+            .mutate_state(Mutation::RevealPlayer(self.player_id, character))
+            .await?;
+        game_logic
+            .broadcast_info(InfoMessage::Basic(format!(
+                "{} has to reveal being Bryan",
+                game_logic.name(self.player_id)
+            )))
+            .await?;
+        Ok(Flow::Continue)
+    }
+}
+
+/// Equipment David collects to win.
+const DAVID_EQUIPMENT: &[&str] = &[
+    "Talisman",
+    "Spear of Longinus",
+    "Holy Robe",
+    "Silver Rosary",
+];
+
+/// Number of David's equipment he needs to win.
+const DAVID_EQUIPMENT_COUNT: usize = 3;
+
+#[derive(Debug)]
+pub(super) struct David;
+impl Character for David {
+    fn name(&self) -> &'static str {
+        "David"
+    }
+
+    fn faction(&self) -> Faction {
+        Faction::Neutral
+    }
+
+    fn hit_point(&self) -> usize {
+        13
+    }
+
+    fn pack(&self) -> Pack {
+        Pack::Expansion
+    }
+
+    /// David wins as soon as he has three of the Talisman, the Spear of
+    /// Longinus, the Holy Robe and the Silver Rosary.
+    fn has_won(&self, player_id: PlayerId, game_logic: &GameLogic, _game_over: bool) -> bool {
+        game_logic
+            .state
+            .player(player_id)
+            .equipment()
+            .iter()
+            .filter(|e| DAVID_EQUIPMENT.contains(&e.name()))
+            .count()
+            >= DAVID_EQUIPMENT_COUNT
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use shared::{
+        state::{EquipmentId, Mutation},
+        Action, Dices, PlayerId, PromptKind,
+    };
+
+    use super::BOB_EQUIPMENT;
+    use crate::{
+        game_logic::test_utils::{
+            choose, game_with, location_with, never_asked, reveal, FixedDamage,
+        },
+        Event, LocationEffect,
+    };
+
+    #[tokio::test]
+    async fn allie_heals_all_her_damage_once() {
+        let allie = PlayerId::new(0);
+        let mut game_logic = game_with(&["Allie", "Gregor"], move |_, _, choices| {
+            choose(choices, Action::HealPlayer(allie, Some(5)))
+        });
+        reveal(&mut game_logic, allie).await;
+        let turn_start = Event::TurnStart { player: allie };
+
+        game_logic.damage(None, allie, 5).await.unwrap();
+        game_logic.dispatch(turn_start.clone()).await.unwrap();
+        assert_eq!(game_logic.state.player(allie).damage(), 0);
+
+        game_logic.damage(None, allie, 5).await.unwrap();
+        game_logic.dispatch(turn_start).await.unwrap();
+        assert_eq!(game_logic.state.player(allie).damage(), 5);
+    }
+
+    #[tokio::test]
+    async fn surviving_neutrals_win_alongside_the_winning_faction() {
+        let mut game_logic = game_with(&["Gregor", "Liche", "Allie", "Bob"], never_asked);
+        let [gregor, liche, allie, bob] = [0, 1, 2, 3].map(PlayerId::new);
+
+        game_logic.damage(None, bob, 13).await.unwrap();
+        assert_eq!(game_logic.winners, None);
+
+        game_logic.damage(Some(gregor), liche, 14).await.unwrap();
+        assert_eq!(game_logic.winners, Some(vec![gregor, allie]));
+    }
+
+    #[tokio::test]
+    async fn bob_robs_equipment_instead_of_dealing_damage() {
+        let equipment = EquipmentId::new(3);
+        let mut game_logic = game_with(&["Bob", "Gregor"], move |_, _, choices| {
+            choose(choices, Action::Equipment(equipment))
+        });
+        let (bob, target) = (PlayerId::new(0), PlayerId::new(1));
+        reveal(&mut game_logic, bob).await;
+        game_logic
+            .mutate_state(Mutation::GiveEquipment(target, equipment))
+            .await
+            .unwrap();
+        let attack = |damage| Event::BeforeAttack {
+            attacker: bob,
+            target,
+            damage,
+        };
+
+        // Too weak an attack to rob
+        let res = game_logic.dispatch(attack(1)).await.unwrap();
+        assert_eq!(res, Some(attack(1)));
+
+        let res = game_logic.dispatch(attack(2)).await.unwrap();
+        assert_eq!(res, None);
+        assert_eq!(game_logic.state.player(bob).equipment(), [equipment]);
+        assert!(game_logic.state.player(target).equipment().is_empty());
+    }
+
+    #[tokio::test]
+    async fn bob_wins_with_enough_equipment() {
+        let mut game_logic = game_with(&["Bob", "Gregor", "Liche"], never_asked);
+        let bob = PlayerId::new(0);
+
+        for i in 0..BOB_EQUIPMENT {
+            assert_eq!(game_logic.winners, None);
+            game_logic
+                .mutate_state(Mutation::GiveEquipment(bob, EquipmentId::new(i)))
+                .await
+                .unwrap();
+        }
+        assert_eq!(game_logic.winners, Some(vec![bob]));
+    }
+
+    #[tokio::test]
+    async fn charles_takes_damage_to_attack_again() {
+        let (charles, target) = (PlayerId::new(0), PlayerId::new(1));
+        let mut feasted = false;
+        let mut game_logic =
+            game_with(
+                &["Charles", "Gregor"],
+                move |_, prompt, choices| match prompt.kind {
+                    PromptKind::Ability if !feasted => {
+                        feasted = true;
+                        choose(choices, Action::DamagePlayer(target, None))
+                    }
+                    PromptKind::Ability => choose(choices, Action::Skip),
+                    _ => choose(choices, Action::DiceRoll(Dices::Both)),
+                },
+            );
+        reveal(&mut game_logic, charles).await;
+        game_logic.register_handler(0, Arc::new(FixedDamage(3)));
+
+        game_logic
+            .dispatch(Event::AfterAttack {
+                attacker: charles,
+                target,
+                damage: 1,
+            })
+            .await
+            .unwrap();
+        // A single feast, declined the second time
+        assert_eq!(game_logic.state.player(charles).damage(), 2);
+        assert_eq!(game_logic.state.player(target).damage(), 3);
+    }
+
+    #[tokio::test]
+    async fn charles_wins_once_he_killed_and_three_are_dead() {
+        let mut game_logic = game_with(
+            &[
+                "Charles", "Gregor", "Emi", "Liche", "Vampire", "Bob", "Allie",
+            ],
+            never_asked,
+        );
+        let [charles, gregor, _, _, _, bob, allie] = [0, 1, 2, 3, 4, 5, 6].map(PlayerId::new);
+
+        game_logic.damage(None, allie, 14).await.unwrap();
+        game_logic.damage(None, gregor, 14).await.unwrap();
+        assert_eq!(game_logic.winners, None);
+
+        game_logic.damage(Some(charles), bob, 14).await.unwrap();
+        assert_eq!(game_logic.winners, Some(vec![charles]));
+    }
+
+    #[tokio::test]
+    async fn catherine_heals_and_wins_dying_first() {
+        let mut game_logic = game_with(&["Catherine", "Gregor", "Liche"], never_asked);
+        let catherine = PlayerId::new(0);
+        let turn_start = Event::TurnStart { player: catherine };
+        game_logic.damage(None, catherine, 3).await.unwrap();
+
+        // Hidden, Catherine does not heal
+        game_logic.dispatch(turn_start.clone()).await.unwrap();
+        assert_eq!(game_logic.state.player(catherine).damage(), 3);
+
+        reveal(&mut game_logic, catherine).await;
+        game_logic.dispatch(turn_start).await.unwrap();
+        assert_eq!(game_logic.state.player(catherine).damage(), 2);
+
+        game_logic.damage(None, catherine, 11).await.unwrap();
+        assert_eq!(game_logic.winners, Some(vec![catherine]));
+    }
+
+    #[tokio::test]
+    async fn catherine_wins_among_the_last_two_alive() {
+        let mut game_logic = game_with(&["Catherine", "Gregor", "Emi", "Liche"], never_asked);
+        let [catherine, gregor, emi, liche] = [0, 1, 2, 3].map(PlayerId::new);
+
+        game_logic.damage(Some(liche), gregor, 14).await.unwrap();
+        assert_eq!(game_logic.winners, None);
+        game_logic.damage(Some(liche), emi, 14).await.unwrap();
+        assert_eq!(game_logic.winners, Some(vec![catherine, liche]));
+    }
+
+    #[tokio::test]
+    async fn agnes_wins_with_the_player_before_her() {
+        let mut game_logic = game_with(&["Liche", "Agnes", "Gregor", "Emi"], never_asked);
+        let [liche, agnes, gregor, emi] = [0, 1, 2, 3].map(PlayerId::new);
+
+        game_logic.damage(None, gregor, 14).await.unwrap();
+        assert_eq!(game_logic.winners, None);
+        game_logic.damage(None, emi, 14).await.unwrap();
+        assert_eq!(game_logic.winners, Some(vec![liche, agnes]));
+    }
+
+    #[tokio::test]
+    async fn bryan_reveals_killing_small_characters_and_wins_killing_big_ones() {
+        let mut game_logic = game_with(&["Bryan", "Allie", "Bob", "Gregor", "Liche"], never_asked);
+        let [bryan, allie, bob, _, _] = [0, 1, 2, 3, 4].map(PlayerId::new);
+
+        game_logic.damage(Some(bryan), allie, 8).await.unwrap();
+        assert!(game_logic.state.player(bryan).revealed());
+        assert_eq!(game_logic.winners, None);
+
+        game_logic.damage(Some(bryan), bob, 13).await.unwrap();
+        assert_eq!(game_logic.winners, Some(vec![bryan]));
+    }
+
+    #[tokio::test]
+    async fn bryan_wins_at_the_erstwhile_altar_when_the_game_ends() {
+        let mut game_logic = game_with(&["Bryan", "Gregor", "Liche", "Allie"], never_asked);
+        let [bryan, gregor, liche, allie] = [0, 1, 2, 3].map(PlayerId::new);
+        let altar = location_with(&game_logic, LocationEffect::StealEquipment);
+        game_logic
+            .mutate_state(Mutation::Move(bryan, altar))
+            .await
+            .unwrap();
+
+        game_logic.damage(Some(gregor), liche, 14).await.unwrap();
+        assert_eq!(game_logic.winners, Some(vec![bryan, gregor, allie]));
+    }
+
+    #[tokio::test]
+    async fn david_wins_with_three_of_his_relics() {
+        let mut game_logic = game_with(&["David", "Gregor", "Liche"], never_asked);
+        let david = PlayerId::new(0);
+        let equipment = |name| EquipmentId::all().find(|e| e.name() == name).unwrap();
+
+        for name in ["Chainsaw", "Talisman", "Holy Robe", "Silver Rosary"] {
+            assert_eq!(game_logic.winners, None);
+            game_logic
+                .mutate_state(Mutation::GiveEquipment(david, equipment(name)))
+                .await
+                .unwrap();
+        }
+        assert_eq!(game_logic.winners, Some(vec![david]));
+    }
+}
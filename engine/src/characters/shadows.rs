@@ -0,0 +1,418 @@
+use std::sync::Arc;
+
+use anyhow::Result;
+use shared::{
+    state::{Faction, Pack, Status},
+    Action, InfoMessage, PlayerId,
+};
+
+use super::{ability_prompt, use_ability, Character};
+use crate::{Event, EventHandler, Flow, GameLogic, LocationEffect};
+
+#[derive(Debug)]
+pub(super) struct Metamorphe;
+impl Character for Metamorphe {
+    fn name(&self) -> &'static str {
+        "Metamorphe"
+    }
+
+    fn faction(&self) -> Faction {
+        Faction::Shadow
+    }
+
+    fn hit_point(&self) -> usize {
+        11
+    }
+
+    fn pack(&self) -> Pack {
+        Pack::Base
+    }
+
+    fn can_lie(&self) -> bool {
+        true
+    }
+}
+
+#[derive(Debug)]
+pub(super) struct Liche;
+impl Character for Liche {
+    fn name(&self) -> &'static str {
+        "Liche"
+    }
+
+    fn faction(&self) -> Faction {
+        Faction::Shadow
+    }
+
+    fn hit_point(&self) -> usize {
+        14
+    }
+
+    fn pack(&self) -> Pack {
+        Pack::Expansion
+    }
+
+    fn register_ability(&self, player_id: PlayerId, game_logic: &mut GameLogic) {
+        game_logic.register_handler(0, Arc::new(Necromancy { player_id }));
+    }
+}
+
+/// Once per game at the end of its turn, the Liche plays an extra turn for
+/// each dead player.
+struct Necromancy {
+    player_id: PlayerId,
+}
+
+#[async_trait::async_trait]
+impl EventHandler for Necromancy {
+    async fn handle(&self, game_logic: &mut GameLogic, event: &mut Event) -> Result<Flow> {
+        if !matches!(*event, Event::TurnEnd { player } if player == self.player_id) {
+            return Ok(Flow::Continue);
+        }
+        let dead = game_logic.state.players().filter(|p| !p.is_alive()).count();
+        if dead == 0 {
+            return Ok(Flow::Continue);
+        }
+        let Some(prompt) = ability_prompt(game_logic, self.player_id, true) else {
+            return Ok(Flow::Continue);
+        };
+        let used = game_logic
+            .message_channel
+            .request_action_map(
+                self.player_id,
+                prompt,
+                [(Action::UseAbility, true), (Action::Skip, false)],
+            )
+            .await?;
+        if used {
+            use_ability(game_logic, self.player_id).await?;
+            game_logic
+                .apply_status(self.player_id, Status::ExtraTurn, dead)
+                .await?;
+        }
+        Ok(Flow::Continue)
+    }
+}
+
+#[derive(Debug)]
+pub(super) struct Vampire;
+impl Character for Vampire {
+    fn name(&self) -> &'static str {
+        "Vampire"
+    }
+
+    fn faction(&self) -> Faction {
+        Faction::Shadow
+    }
+
+    fn hit_point(&self) -> usize {
+        13
+    }
+
+    fn pack(&self) -> Pack {
+        Pack::Base
+    }
+
+    fn register_ability(&self, player_id: PlayerId, game_logic: &mut GameLogic) {
+        game_logic.register_handler(0, Arc::new(Bite { player_id }));
+    }
+}
+
+/// When the Vampire deals damage with an attack, it heals 2 damage.
+struct Bite {
+    player_id: PlayerId,
+}
+
+#[async_trait::async_trait]
+impl EventHandler for Bite {
+    async fn handle(&self, game_logic: &mut GameLogic, event: &mut Event) -> Result<Flow> {
+        if !matches!(
+            *event,
+            Event::AfterAttack { attacker, damage, .. } if attacker == self.player_id && damage > 0
+        ) || ability_prompt(game_logic, self.player_id, false).is_none()
+        {
+            return Ok(Flow::Continue);
+        }
+        game_logic
+            .heal(Some(self.player_id), self.player_id, 2)
+            .await?;
+        Ok(Flow::Continue)
+    }
+}
+
+#[derive(Debug)]
+pub(super) struct Werewolf;
+impl Character for Werewolf {
+    fn name(&self) -> &'static str {
+        "Werewolf"
+    }
+
+    fn faction(&self) -> Faction {
+        Faction::Shadow
+    }
+
+    fn hit_point(&self) -> usize {
+        14
+    }
+
+    fn pack(&self) -> Pack {
+        Pack::Base
+    }
+
+    fn register_ability(&self, player_id: PlayerId, game_logic: &mut GameLogic) {
+        game_logic.register_handler(0, Arc::new(Counterattack { player_id }));
+    }
+}
+
+/// After being attacked, the Werewolf can immediately attack back.
+struct Counterattack {
+    player_id: PlayerId,
+}
+
+#[async_trait::async_trait]
+impl EventHandler for Counterattack {
+    async fn handle(&self, game_logic: &mut GameLogic, event: &mut Event) -> Result<Flow> {
+        let Event::AfterAttack {
+            attacker, target, ..
+        } = *event
+        else {
+            return Ok(Flow::Continue);
+        };
+        if target != self.player_id || !game_logic.state.player(attacker).is_alive() {
+            return Ok(Flow::Continue);
+        }
+        let Some(prompt) = ability_prompt(game_logic, self.player_id, false) else {
+            return Ok(Flow::Continue);
+        };
+        let counterattack = game_logic
+            .message_channel
+            .request_action_map(
+                self.player_id,
+                prompt,
+                [
+                    (Action::DamagePlayer(attacker, None), true),
+                    (Action::Skip, false),
+                ],
+            )
+            .await?;
+        if counterattack {
+            game_logic
+                .broadcast_info(InfoMessage::Basic(format!(
+                    "{} counterattacks {}",
+                    game_logic.name(self.player_id),
+                    game_logic.name(attacker)
+                )))
+                .await?;
+            game_logic.attack_player(self.player_id, attacker).await?;
+        }
+        Ok(Flow::Continue)
+    }
+}
+
+#[derive(Debug)]
+pub(super) struct UltraSoul;
+impl Character for UltraSoul {
+    fn name(&self) -> &'static str {
+        "Ultra Soul"
+    }
+
+    fn faction(&self) -> Faction {
+        Faction::Shadow
+    }
+
+    fn hit_point(&self) -> usize {
+        11
+    }
+
+    fn pack(&self) -> Pack {
+        Pack::Expansion
+    }
+
+    fn register_ability(&self, player_id: PlayerId, game_logic: &mut GameLogic) {
+        game_logic.register_handler(0, Arc::new(MurderRay { player_id }));
+    }
+}
+
+/// At the start of his turn, Ultra Soul can deal 3 damage to a player in
+/// the Underworld Gate.
+struct MurderRay {
+    player_id: PlayerId,
+}
+
+/// Damage dealt by Ultra Soul's ray.
+const MURDER_RAY_DAMAGE: usize = 3;
+
+#[async_trait::async_trait]
+impl EventHandler for MurderRay {
+    async fn handle(&self, game_logic: &mut GameLogic, event: &mut Event) -> Result<Flow> {
+        if !matches!(*event, Event::TurnStart { player } if player == self.player_id) {
+            return Ok(Flow::Continue);
+        }
+        let targets = game_logic
+            .state
+            .players()
+            .filter(|p| {
+                p.is_alive()
+                    && p.id() != self.player_id
+                    && p.location().is_some_and(|l| {
+                        *game_logic.location_effect(l.id()) == LocationEffect::AnyCard
+                    })
+            })
+            .map(|p| {
+                (
+                    Action::DamagePlayer(p.id(), Some(MURDER_RAY_DAMAGE)),
+                    Some(p.id()),
+                )
+            })
+            .collect::<Vec<_>>();
+        if targets.is_empty() {
+            return Ok(Flow::Continue);
+        }
+        let Some(prompt) = ability_prompt(game_logic, self.player_id, false) else {
+            return Ok(Flow::Continue);
+        };
+        let target = game_logic
+            .message_channel
+            .request_action_map(
+                self.player_id,
+                prompt,
+                targets.into_iter().chain([(Action::Skip, None)]),
+            )
+            .await?;
+        if let Some(target) = target {
+            game_logic
+                .damage(Some(self.player_id), target, MURDER_RAY_DAMAGE)
+                .await?;
+        }
+        Ok(Flow::Continue)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use shared::{
+        state::{Mutation, Status},
+        Action, Dices, PlayerId,
+    };
+
+    use crate::{
+        game_logic::test_utils::{
+            choose, game_with, location_with, never_asked, reveal, FixedDamage,
+        },
+        Event, LocationEffect,
+    };
+
+    #[tokio::test]
+    async fn liche_plays_extra_turns_for_dead_players() {
+        let mut game_logic = game_with(&["Liche", "Gregor", "Franklin"], |_, _, choices| {
+            choose(choices, Action::UseAbility)
+        });
+        let liche = PlayerId::new(0);
+        reveal(&mut game_logic, liche).await;
+        let turn_end = Event::TurnEnd { player: liche };
+
+        // Nobody is dead yet, the ability is kept for later
+        game_logic.dispatch(turn_end.clone()).await.unwrap();
+        assert!(!game_logic.state.player(liche).ability_used());
+
+        game_logic.damage(None, PlayerId::new(1), 14).await.unwrap();
+        game_logic.dispatch(turn_end.clone()).await.unwrap();
+        assert!(game_logic.state.player(liche).ability_used());
+        game_logic.dispatch(turn_end).await.unwrap();
+
+        // One extra turn for the dead player
+        game_logic.next_player().await.unwrap();
+        assert_eq!(game_logic.state.current_player().id(), liche);
+        assert!(!game_logic.state.player(liche).has_status(Status::ExtraTurn));
+        game_logic.next_player().await.unwrap();
+        assert_eq!(game_logic.state.current_player().id(), PlayerId::new(2));
+    }
+
+    #[tokio::test]
+    async fn vampire_heals_when_its_attacks_deal_damage() {
+        let mut game_logic = game_with(&["Vampire", "Gregor"], never_asked);
+        let (vampire, target) = (PlayerId::new(0), PlayerId::new(1));
+        reveal(&mut game_logic, vampire).await;
+        game_logic.damage(None, vampire, 3).await.unwrap();
+
+        for damage in [0, 2] {
+            game_logic
+                .dispatch(Event::AfterAttack {
+                    attacker: vampire,
+                    target,
+                    damage,
+                })
+                .await
+                .unwrap();
+        }
+        assert_eq!(game_logic.state.player(vampire).damage(), 1);
+    }
+
+    #[tokio::test]
+    async fn werewolf_counterattacks_its_attacker() {
+        let (werewolf, attacker) = (PlayerId::new(0), PlayerId::new(1));
+        let mut game_logic = game_with(&["Werewolf", "Gregor"], move |player, _, choices| {
+            assert_eq!(player, werewolf);
+            if choices.contains(&Action::DiceRoll(Dices::Both)) {
+                choose(choices, Action::DiceRoll(Dices::Both))
+            } else {
+                choose(choices, Action::DamagePlayer(attacker, None))
+            }
+        });
+        let attack = Event::AfterAttack {
+            attacker,
+            target: werewolf,
+            damage: 1,
+        };
+
+        game_logic.register_handler(0, Arc::new(FixedDamage(3)));
+
+        // Hidden, the Werewolf cannot counterattack
+        game_logic.dispatch(attack.clone()).await.unwrap();
+        assert_eq!(game_logic.state.player(attacker).damage(), 0);
+
+        reveal(&mut game_logic, werewolf).await;
+        game_logic.dispatch(attack).await.unwrap();
+        assert_eq!(game_logic.state.player(attacker).damage(), 3);
+        assert_eq!(game_logic.state.player(werewolf).damage(), 0);
+    }
+
+    #[tokio::test]
+    async fn ultra_soul_rays_players_in_the_underworld_gate() {
+        let [ultra_soul, gregor, emi] = [0, 1, 2].map(PlayerId::new);
+        let mut game_logic = game_with(&["Ultra Soul", "Gregor", "Emi"], move |_, _, choices| {
+            choose(choices, Action::DamagePlayer(emi, Some(3)))
+        });
+        reveal(&mut game_logic, ultra_soul).await;
+        let gate = location_with(&game_logic, LocationEffect::AnyCard);
+        let church = location_with(&game_logic, LocationEffect::WhiteCard);
+        game_logic
+            .mutate_state(Mutation::Move(gregor, church))
+            .await
+            .unwrap();
+        game_logic
+            .mutate_state(Mutation::Move(emi, gate))
+            .await
+            .unwrap();
+
+        game_logic
+            .dispatch(Event::TurnStart { player: ultra_soul })
+            .await
+            .unwrap();
+        assert_eq!(game_logic.state.player(emi).damage(), 3);
+        assert_eq!(game_logic.state.player(gregor).damage(), 0);
+
+        // Nobody left in the Underworld Gate to ray
+        game_logic
+            .mutate_state(Mutation::Move(emi, church))
+            .await
+            .unwrap();
+        game_logic
+            .dispatch(Event::TurnStart { player: ultra_soul })
+            .await
+            .unwrap();
+        assert_eq!(game_logic.state.player(emi).damage(), 3);
+    }
+}
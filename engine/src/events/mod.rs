@@ -0,0 +1,191 @@
+use std::sync::Arc;
+
+use anyhow::Result;
+use shared::{LocationId, PlayerId};
+
+use crate::GameLogic;
+
+/// Something happening in the game that handlers can react to. Events sent
+/// before their effect is applied can be modified or cancelled by handlers.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Event {
+    TurnStart {
+        player: PlayerId,
+    },
+    TurnEnd {
+        player: PlayerId,
+    },
+    /// An attack hit and is about to deal `damage` to `target`.
+    BeforeAttack {
+        attacker: PlayerId,
+        target: PlayerId,
+        damage: usize,
+    },
+    /// An attack is over, `damage` being what it actually dealt.
+    AfterAttack {
+        attacker: PlayerId,
+        target: PlayerId,
+        damage: usize,
+    },
+    /// Damage is about to be dealt, `source` is `None` for damage not coming
+    /// from a player.
+    Damage {
+        source: Option<PlayerId>,
+        target: PlayerId,
+        amount: usize,
+    },
+    /// Damage has been dealt, cancelling it has no effect.
+    AfterDamage {
+        source: Option<PlayerId>,
+        target: PlayerId,
+        amount: usize,
+    },
+    Heal {
+        source: Option<PlayerId>,
+        target: PlayerId,
+        amount: usize,
+    },
+    /// The player is about to move. Setting `destination` moves it there
+    /// instead of rolling the dice.
+    Movement {
+        player: PlayerId,
+        destination: Option<LocationId>,
+    },
+    Move {
+        player: PlayerId,
+        location: LocationId,
+    },
+    /// A player died, cancelling it has no effect.
+    Death {
+        player: PlayerId,
+        killer: Option<PlayerId>,
+    },
+    /// A player drew the card named `card`. Cancelling it discards the card
+    /// without effect.
+    CardDrawn {
+        player: PlayerId,
+        card: String,
+    },
+}
+
+/// What should happen to an event once a handler is done with it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Flow {
+    Continue,
+    /// Stop the event: remaining handlers are not called and its effect is
+    /// not applied.
+    Cancel,
+}
+
+#[async_trait::async_trait]
+pub trait EventHandler: Send + Sync {
+    async fn handle(&self, game_logic: &mut GameLogic, event: &mut Event) -> Result<Flow>;
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct HandlerId(usize);
+
+/// Handlers registered for game events, called by decreasing priority and
+/// by registration order for equal priorities.
+#[derive(Default)]
+pub(crate) struct EventBus {
+    handlers: Vec<(HandlerId, i32, Arc<dyn EventHandler>)>,
+    next_id: usize,
+}
+
+impl std::fmt::Debug for EventBus {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("EventBus")
+            .field("handlers", &self.handlers.len())
+            .finish_non_exhaustive()
+    }
+}
+
+impl EventBus {
+    pub(crate) fn register(&mut self, priority: i32, handler: Arc<dyn EventHandler>) -> HandlerId {
+        let id = HandlerId(self.next_id);
+        self.next_id += 1;
+        let position = self
+            .handlers
+            .iter()
+            .position(|&(_, p, _)| p < priority)
+            .unwrap_or(self.handlers.len());
+        self.handlers.insert(position, (id, priority, handler));
+        id
+    }
+
+    pub(crate) fn unregister(&mut self, id: HandlerId) {
+        self.handlers.retain(|&(i, _, _)| i != id);
+    }
+
+    pub(crate) fn handlers(&self) -> Vec<Arc<dyn EventHandler>> {
+        self.handlers
+            .iter()
+            .map(|(_, _, h)| Arc::clone(h))
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::game_logic::test_utils::{game_with, never_asked};
+
+    struct Double;
+
+    #[async_trait::async_trait]
+    impl EventHandler for Double {
+        async fn handle(&self, _game_logic: &mut GameLogic, event: &mut Event) -> Result<Flow> {
+            if let Event::Damage { amount, .. } = event {
+                *amount *= 2;
+            }
+            Ok(Flow::Continue)
+        }
+    }
+
+    struct SetToOne;
+
+    #[async_trait::async_trait]
+    impl EventHandler for SetToOne {
+        async fn handle(&self, _game_logic: &mut GameLogic, event: &mut Event) -> Result<Flow> {
+            if let Event::Damage { amount, .. } = event {
+                *amount = 1;
+            }
+            Ok(Flow::Continue)
+        }
+    }
+
+    struct CancelHeal;
+
+    #[async_trait::async_trait]
+    impl EventHandler for CancelHeal {
+        async fn handle(&self, _game_logic: &mut GameLogic, event: &mut Event) -> Result<Flow> {
+            Ok(match event {
+                Event::Heal { .. } => Flow::Cancel,
+                _ => Flow::Continue,
+            })
+        }
+    }
+
+    #[tokio::test]
+    async fn handlers_modify_and_cancel_events_in_priority_order() {
+        // Characters without abilities, so that only the handlers of the
+        // test react
+        let mut game_logic = game_with(&["Metamorphe", "Agnes", "David"], never_asked);
+        let player = PlayerId::new(0);
+
+        game_logic.register_handler(0, Arc::new(Double));
+        let set_to_one = game_logic.register_handler(10, Arc::new(SetToOne));
+        game_logic.register_handler(0, Arc::new(CancelHeal));
+
+        game_logic.damage(None, player, 5).await.unwrap();
+        assert_eq!(game_logic.state.player(player).damage(), 2);
+
+        game_logic.heal(None, player, 1).await.unwrap();
+        assert_eq!(game_logic.state.player(player).damage(), 2);
+
+        game_logic.unregister_handler(set_to_one);
+        game_logic.damage(None, player, 3).await.unwrap();
+        assert_eq!(game_logic.state.player(player).damage(), 8);
+    }
+}
@@ -1,5 +1,5 @@
 use rand::prelude::*;
-use shared::Roll;
+use shared::{Dices, Roll};
 
 #[derive(Debug)]
 pub struct Dice {
@@ -21,10 +21,35 @@ impl Dice {
         self.rng.gen_range(1..=6)
     }
 
-    pub fn roll(&mut self) -> Roll {
-        Roll {
-            d4: self.roll_d4(),
-            d6: self.roll_d6(),
+    pub fn roll(&mut self, dices: Dices) -> Roll {
+        match dices {
+            Dices::D4 => Roll::D4(self.roll_d4()),
+            Dices::D6 => Roll::D6(self.roll_d6()),
+            Dices::Both => Roll::Both {
+                d4: self.roll_d4(),
+                d6: self.roll_d6(),
+            },
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn roll_only_requested_dices() {
+        let mut dice = Dice::new();
+        for _ in 0..100 {
+            match dice.roll(Dices::D4) {
+                Roll::D4(v) => assert!((1..=4).contains(&v)),
+                r => panic!("Unexpected roll {:?}", r),
+            }
+            match dice.roll(Dices::D6) {
+                Roll::D6(v) => assert!((1..=6).contains(&v)),
+                r => panic!("Unexpected roll {:?}", r),
+            }
+            assert_eq!(dice.roll(Dices::Both).dices(), Dices::Both);
         }
     }
 }
@@ -1,23 +1,37 @@
-use anyhow::Result;
-use rand::{seq::SliceRandom, thread_rng};
+use anyhow::{bail, ensure, Result};
+use rand::{seq::SliceRandom, thread_rng, Rng};
 use shared::{
     state::Mutation,
-    state::{Location, State},
-    Action, Dices, InfoMessage, PlayerId,
+    state::{Location, Pack, PlayerStorage, State, Status, StatusEffect},
+    Action, CharacterId, Dices, InfoMessage, LocationId, PlayerId, Prompt, PromptKind, RequestId,
+    Roll,
 };
 
+use std::{ops::RangeInclusive, sync::Arc};
+
 use tokio::sync::{mpsc, oneshot};
 
 mod dice;
+#[cfg(test)]
+pub(crate) mod test_utils;
 
 pub(crate) use dice::Dice;
 
-use crate::locations::{location_behaviors, LocationBehavior};
+use crate::{
+    characters::Characters,
+    events::{Event, EventBus, EventHandler, Flow, HandlerId},
+    locations::{Board, LocationEffect},
+};
+
+/// Numbers of players a game can be played with.
+pub const PLAYER_COUNTS: RangeInclusive<usize> = 4..=8;
 
 #[derive(Debug)]
 pub enum Command {
     ActionRequest {
+        id: RequestId,
         player: PlayerId,
+        prompt: Prompt,
         choices: Vec<Action>,
         response: oneshot::Sender<usize>,
     },
@@ -26,13 +40,20 @@ pub enum Command {
         payload: InfoMessage,
     },
     StateMutation(Mutation),
+    /// Full, unredacted state of the game. Sent once when the game starts,
+    /// every following change is notified through `StateMutation`.
+    StateSync(Box<State>),
 }
 
 pub struct GameLogic {
     pub(crate) message_channel: MessageChannel,
     pub(crate) state: State,
     pub(crate) dice: Dice,
-    pub(crate) location_behaviors: [&'static dyn LocationBehavior; 6],
+    /// Effect of every location, by id.
+    pub(crate) location_effects: Vec<LocationEffect>,
+    pub(crate) event_bus: EventBus,
+    /// Set once a player fulfilled its victory condition.
+    pub(crate) winners: Option<Vec<PlayerId>>,
 }
 
 impl std::fmt::Debug for GameLogic {
@@ -41,114 +62,324 @@ impl std::fmt::Debug for GameLogic {
             .field("message_channel", &self.message_channel)
             .field("state", &self.state)
             .field("dice", &self.dice)
+            .field("event_bus", &self.event_bus)
             .finish_non_exhaustive()
     }
 }
 
 impl GameLogic {
-    pub fn new(player_count: usize, command_channel: mpsc::Sender<Command>) -> Self {
-        let location_behaviors = location_behaviors();
+    /// Game dealing characters from the given packs, failing if they do not
+    /// have enough characters for the players.
+    pub fn new(
+        names: Vec<String>,
+        packs: Vec<Pack>,
+        board: Board,
+        command_channel: mpsc::Sender<Command>,
+    ) -> Result<Self> {
+        ensure!(
+            PLAYER_COUNTS.contains(&names.len()),
+            "Games are played with {} to {} players",
+            PLAYER_COUNTS.start(),
+            PLAYER_COUNTS.end()
+        );
+        let first_player = PlayerId::new(thread_rng().gen_range(0..names.len()));
+        Ok(Self::with_characters(
+            Characters::generate(names.len(), &packs)?,
+            names,
+            packs,
+            first_player,
+            board,
+            command_channel,
+        ))
+    }
+
+    /// Game where the `i`th player, named after the `i`th name, plays the
+    /// `i`th character.
+    pub(crate) fn with_characters(
+        characters: Vec<CharacterId>,
+        names: Vec<String>,
+        packs: Vec<Pack>,
+        first_player: PlayerId,
+        board: Board,
+        command_channel: mpsc::Sender<Command>,
+    ) -> Self {
+        let location_effects = board.locations.iter().map(|l| l.effect.clone()).collect();
 
         let locations = {
-            let locations = {
-                let mut i = 0;
-                location_behaviors.map(|lb| {
-                    let res = Location::new(i, lb.name().to_owned(), lb.dice_numbers());
-                    i += 1;
-                    res
-                })
-            };
-            let mut layout = locations.map(|l| l.id());
+            let locations = board
+                .locations
+                .into_iter()
+                .enumerate()
+                .map(|(i, l)| Location::new(i, l.name, l.description, l.dice_numbers))
+                .collect::<Vec<_>>();
+            let mut layout = locations.iter().map(|l| l.id()).collect::<Vec<_>>();
             layout.shuffle(&mut rand::thread_rng());
 
-            shared::state::Locations::new(locations, layout)
+            shared::state::Locations::new(locations, layout, board.group_sizes)
         };
 
-        GameLogic {
-            message_channel: MessageChannel(command_channel),
-            state: State::new(players, locations, first_player, characters),
+        let players = characters
+            .iter()
+            .zip(names)
+            .enumerate()
+            .map(|(i, (&c, name))| PlayerStorage::new(PlayerId::new(i), name, c))
+            .collect();
+
+        let mut game_logic = GameLogic {
+            message_channel: MessageChannel::new(command_channel),
+            state: State::new(
+                players,
+                locations,
+                first_player,
+                Characters::states(),
+                packs,
+            ),
             dice: Dice::new(),
-            location_behaviors,
+            location_effects,
+            event_bus: EventBus::default(),
+            winners: None,
+        };
+        for (i, &c) in characters.iter().enumerate() {
+            Characters::from_id(c).register_ability(PlayerId::new(i), &mut game_logic);
         }
+        game_logic
+    }
+
+    /// Registers a handler called on every game event. Handlers with a
+    /// higher priority are called first.
+    pub fn register_handler(&mut self, priority: i32, handler: Arc<dyn EventHandler>) -> HandlerId {
+        self.event_bus.register(priority, handler)
+    }
+
+    pub fn unregister_handler(&mut self, id: HandlerId) {
+        self.event_bus.unregister(id)
     }
 
-    pub async fn run(&mut self) -> Result<()> {
+    /// Plays the game until it is over, and returns the winners.
+    pub async fn run(&mut self) -> Result<Vec<PlayerId>> {
+        self.message_channel
+            .send(Command::StateSync(Box::new(self.state.clone())))
+            .await?;
         loop {
-            self.movement().await?;
-            self.attack().await?;
+            self.play_turn().await?;
+            if let Some(winners) = self.winners.clone() {
+                self.broadcast_info(InfoMessage::GameOver {
+                    winners: winners.clone(),
+                })
+                .await?;
+                return Ok(winners);
+            }
             self.next_player().await?;
         }
     }
 
+    /// Plays the turn of the current player, stopping as soon as the game
+    /// is over.
+    async fn play_turn(&mut self) -> Result<()> {
+        self.reveal_window(self.state.current_player().id()).await?;
+        self.dispatch(Event::TurnStart {
+            player: self.state.current_player().id(),
+        })
+        .await?;
+        if self.winners.is_some() || !self.state.current_player().is_alive() {
+            return Ok(());
+        }
+        self.movement().await?;
+        if self.winners.is_some() {
+            return Ok(());
+        }
+        // The current player may have died from its location's effect
+        if self.state.current_player().is_alive() {
+            self.attack().await?;
+            if self.winners.is_some() {
+                return Ok(());
+            }
+        }
+        self.dispatch(Event::TurnEnd {
+            player: self.state.current_player().id(),
+        })
+        .await?;
+        Ok(())
+    }
+
     async fn attack(&mut self) -> Result<(), anyhow::Error> {
         let attackable_locations = self
             .state
             .locations()
-            .in_group_iter(self.state.current_player().location().id())
+            .in_group_iter(self.current_location().id())
             .map(|l| l.id())
             .collect::<Vec<_>>();
+        let attack_anyone = self.state.current_player().has_status(Status::AttackAnyone);
         let attackable_players = self
             .state
             .players()
-            .filter(|p| attackable_locations.contains(&p.location().id()))
+            .filter(|p| {
+                attack_anyone
+                    || p.location()
+                        .is_some_and(|l| attackable_locations.contains(&l.id()))
+            })
             .filter(|p| p.id() != self.state.current_player().id())
+            .filter(|p| p.is_alive())
             .map(|p| (Action::DamagePlayer(p.id(), None), Some(p.id())));
         if let Some(player_id) = self
             .message_channel
             .request_action_map(
                 self.state.current_player().id(),
+                Prompt::new(PromptKind::AttackTarget),
                 attackable_players.chain(std::iter::once((Action::Skip, None))),
             )
             .await?
         {
             self.broadcast_info(InfoMessage::Basic(format!(
-                "{:?} is preparing an attack on {:?}",
-                self.state.current_player().id(),
-                player_id
+                "{} is preparing an attack on {}",
+                self.state.current_player().name(),
+                self.name(player_id)
             )))
             .await?;
 
-            self.message_channel
-                .request_action_map(
-                    self.state.current_player().id(),
-                    [(Action::DiceRoll(Dices::Both), ())],
-                )
-                .await?;
-
-            let roll = self.dice.roll();
-            self.broadcast_info(InfoMessage::Roll {
-                from: self.state.current_player().id(),
-                roll,
-            })
-            .await?;
-            let damage = roll.diff();
-            self.mutate_state(Mutation::DamagePlayer(player_id, damage))
+            self.attack_player(self.state.current_player().id(), player_id)
                 .await?;
         } else {
             self.broadcast_info(InfoMessage::Basic(format!(
-                "{:?} did not attack",
-                self.state.current_player().id()
+                "{} did not attack",
+                self.state.current_player().name()
             )))
             .await?;
         }
         Ok(())
     }
 
+    /// Rolls the attack dice and deals the resulting damage, giving handlers
+    /// the chance to modify or cancel the attack before it hits and to react
+    /// once it did.
+    pub(crate) async fn attack_player(
+        &mut self,
+        attacker: PlayerId,
+        target: PlayerId,
+    ) -> Result<()> {
+        let roll = self
+            .request_roll(
+                attacker,
+                Prompt::new(PromptKind::AttackRoll),
+                &[Dices::Both],
+            )
+            .await?;
+        match self
+            .dispatch(Event::BeforeAttack {
+                attacker,
+                target,
+                damage: roll.diff(),
+            })
+            .await?
+        {
+            Some(Event::BeforeAttack { target, damage, .. }) => {
+                let damage = self.damage(Some(attacker), target, damage).await?;
+                self.dispatch(Event::AfterAttack {
+                    attacker,
+                    target,
+                    damage,
+                })
+                .await?;
+            }
+            Some(e) => bail!("Handler changed the kind of {:?}", e),
+            None => {
+                self.broadcast_info(InfoMessage::Basic(format!(
+                    "The attack of {} was cancelled",
+                    self.name(attacker)
+                )))
+                .await?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Lets a player reveal its character if it has not done so yet.
+    async fn reveal_window(&mut self, player_id: PlayerId) -> Result<()> {
+        if self.state.player(player_id).revealed() {
+            return Ok(());
+        }
+        let reveal = self
+            .message_channel
+            .request_action_map(
+                player_id,
+                Prompt::new(PromptKind::RevealWindow),
+                [(Action::Skip, false), (Action::Reveal, true)],
+            )
+            .await?;
+        if reveal {
+            let character = self
+                .state
+                .player(player_id)
+                .character()
+                .expect("The engine knows every character");
+            let message = format!(
+                "{} reveals being {}",
+                self.name(player_id),
+                character.name()
+            );
+            let character = character.id();
+            self.mutate_state(Mutation::RevealPlayer(player_id, character))
+                .await?;
+            self.broadcast_info(InfoMessage::Basic(message)).await?;
+        }
+        Ok(())
+    }
+
     async fn movement(&mut self) -> Result<(), anyhow::Error> {
+        let player = self.state.current_player().id();
+        let destination = match self
+            .dispatch(Event::Movement {
+                player,
+                destination: None,
+            })
+            .await?
+        {
+            Some(Event::Movement { destination, .. }) => destination,
+            Some(e) => bail!("Handler changed the kind of {:?}", e),
+            None => return Ok(()),
+        };
+        let location_id = match destination {
+            Some(location_id) => location_id,
+            None => self.roll_destination().await?,
+        };
+
+        let location_id = match self
+            .move_player(self.state.current_player().id(), location_id)
+            .await?
+        {
+            Some(location_id) => location_id,
+            None => return Ok(()),
+        };
+
+        self.location_effect(location_id)
+            .behavior()
+            .handle(self, self.state.current_player().id())
+            .await
+    }
+
+    /// What the location does, which unlike its name is the same on every
+    /// board.
+    pub(crate) fn location_effect(&self, location_id: LocationId) -> &LocationEffect {
+        &self.location_effects[usize::from(location_id)]
+    }
+
+    async fn roll_destination(&mut self) -> Result<LocationId> {
         self.message_channel
             .request_action_map(
                 self.state.current_player().id(),
+                Prompt::new(PromptKind::MovementRoll),
                 [(Action::DiceRoll(Dices::Both), ())],
             )
             .await?;
+        // Roll again when landing on the current location, or on a number
+        // that no location of a custom board uses
         let roll = loop {
-            let roll = self.dice.roll();
-            if !self
-                .state
-                .current_player()
-                .location()
-                .dice_numbers()
-                .contains(&roll.sum())
+            let roll = self.dice.roll(Dices::Both);
+            let destination = self.state.locations().try_from_dice_number(roll.sum());
+            if roll.sum() == 7
+                || destination.is_some_and(|d| {
+                    Some(d.id()) != self.state.current_player().location().map(|l| l.id())
+                })
             {
                 break roll;
             }
@@ -164,50 +395,310 @@ impl GameLogic {
                 .state
                 .locations()
                 .iter()
-                .filter(|l| l.id() != self.state.current_player().location().id())
+                .filter(|l| Some(l.id()) != self.state.current_player().location().map(|l| l.id()))
                 .map(|l| (Action::Location(l.id()), l.id()));
             self.message_channel
-                .request_action_map(self.state.current_player().id(), choices)
+                .request_action_map(
+                    self.state.current_player().id(),
+                    Prompt::new(PromptKind::LocationChoice),
+                    choices,
+                )
                 .await?
         } else {
             self.state.locations().from_dice_number(roll.sum()).id()
         };
-        self.mutate_state(Mutation::Move(
-            self.state.current_player().id(),
-            location_id,
-        ))
-        .await?;
+        Ok(location_id)
+    }
 
-        self.location_behaviors[usize::from(location_id)]
-            .handle(self, self.state.current_player().id())
-            .await;
+    /// Moves on to the next player, or lets the current one play again if it
+    /// has an extra turn. Players skipping their turn are passed over, and
+    /// the statuses of the player whose turn starts count down.
+    pub(crate) async fn next_player(&mut self) -> Result<(), anyhow::Error> {
+        let current = self.state.current_player();
+        let extra_turn = current.is_alive() && current.has_status(Status::ExtraTurn);
+        let current = current.id();
+        let mut next = if extra_turn {
+            self.broadcast_info(InfoMessage::Basic(format!(
+                "{} plays an extra turn",
+                self.name(current)
+            )))
+            .await?;
+            current
+        } else {
+            self.following_player(current)
+        };
+        loop {
+            let skip = self.state.player(next).has_status(Status::SkipTurn);
+            self.count_down_statuses(next).await?;
+            if !skip {
+                break;
+            }
+            self.broadcast_info(InfoMessage::Basic(format!(
+                "{} skips its turn",
+                self.name(next)
+            )))
+            .await?;
+            next = self.following_player(next);
+        }
+        if next != self.state.current_player().id() {
+            self.mutate_state(Mutation::ChangeCurrentPlayer(next))
+                .await?;
+        }
         Ok(())
     }
 
-    async fn next_player(&mut self) -> Result<(), anyhow::Error> {
-        let p = self
-            .state
+    /// First alive player after the given one in turn order.
+    fn following_player(&self, player_id: PlayerId) -> PlayerId {
+        self.state
             .players()
             .cycle() // Make the iterator cycle so we can loop back from last player to first
-            .skip_while(|p| p.id() != self.state.current_player().id()) // Find current player
+            .skip_while(|p| p.id() != player_id) // Find current player
             .skip(1) // Skip him
             .take(self.state.players().len() - 1) // Avoid looping back to current player
             .find(|p| p.is_alive())
-            .expect("If there are no other players, the game should be over");
-        self.mutate_state(Mutation::ChangeCurrentPlayer(p.id()))
+            .expect("If there are no other players, the game should be over")
+            .id()
+    }
+
+    pub(crate) async fn apply_status(
+        &mut self,
+        player_id: PlayerId,
+        status: Status,
+        turns: usize,
+    ) -> Result<()> {
+        self.mutate_state(Mutation::ApplyStatus(
+            player_id,
+            StatusEffect::new(status, turns),
+        ))
+        .await
+    }
+
+    /// A turn of the player starts: its statuses last one turn less, and
+    /// expire once they reach zero.
+    async fn count_down_statuses(&mut self, player_id: PlayerId) -> Result<()> {
+        for effect in self.state.player(player_id).statuses().to_vec() {
+            if effect.turns() <= 1 {
+                self.mutate_state(Mutation::ExpireStatus(player_id, effect.status()))
+                    .await?;
+            } else {
+                self.apply_status(player_id, effect.status(), effect.turns() - 1)
+                    .await?;
+            }
+        }
+        Ok(())
+    }
+
+    pub(crate) fn name(&self, player_id: PlayerId) -> &str {
+        self.state.player(player_id).name()
+    }
+
+    /// Location of the current player, who always has one once the movement
+    /// phase of its turn is over.
+    fn current_location(&self) -> &Location {
+        self.state
+            .current_player()
+            .location()
+            .expect("Current player has not moved yet")
+    }
+
+    /// Asks a player to roll one of the proposed dice, then rolls it and
+    /// tells the table the result. Proposing several dice lets the player
+    /// choose which one to roll.
+    pub(crate) async fn request_roll(
+        &mut self,
+        player_id: PlayerId,
+        prompt: Prompt,
+        dices: &[Dices],
+    ) -> Result<Roll> {
+        let dices = self
+            .message_channel
+            .request_action_map(
+                player_id,
+                prompt,
+                dices.iter().map(|&d| (Action::DiceRoll(d), d)),
+            )
+            .await?;
+        let roll = self.dice.roll(dices);
+        self.broadcast_info(InfoMessage::Roll {
+            from: player_id,
+            roll,
+        })
+        .await?;
+        Ok(roll)
+    }
+
+    /// Sends an event to every registered handler, and returns it as modified
+    /// by them, or `None` if a handler cancelled it.
+    pub(crate) async fn dispatch(&mut self, mut event: Event) -> Result<Option<Event>> {
+        for handler in self.event_bus.handlers() {
+            if handler.handle(self, &mut event).await? == Flow::Cancel {
+                return Ok(None);
+            }
+        }
+        Ok(Some(event))
+    }
+
+    /// Deals damage and returns how much was actually dealt once handlers
+    /// modified or cancelled it.
+    pub(crate) async fn damage(
+        &mut self,
+        source: Option<PlayerId>,
+        target: PlayerId,
+        amount: usize,
+    ) -> Result<usize> {
+        if self.state.player(target).has_status(Status::Immune) {
+            return Ok(0);
+        }
+        let event = Event::Damage {
+            source,
+            target,
+            amount,
+        };
+        let Some(Event::Damage {
+            source,
+            target,
+            amount,
+        }) = self.dispatch(event).await?
+        else {
+            return Ok(0);
+        };
+        self.mutate_state(Mutation::DamagePlayer(target, amount))
+            .await?;
+        self.dispatch(Event::AfterDamage {
+            source,
+            target,
+            amount,
+        })
+        .await?;
+
+        let target = self.state.player(target);
+        if target.is_alive() && target.is_beyond_hit_points() {
+            self.resolve_death(target.id(), source).await?;
+        }
+        Ok(amount)
+    }
+
+    /// Reveals the dead player, records its death and gives its equipment to
+    /// its killer.
+    async fn resolve_death(&mut self, player_id: PlayerId, killer: Option<PlayerId>) -> Result<()> {
+        let player = self.state.player(player_id);
+        if !player.revealed() {
+            let character = player
+                .character()
+                .expect("The engine knows every character")
+                .id();
+            self.mutate_state(Mutation::RevealPlayer(player_id, character))
+                .await?;
+        }
+        self.mutate_state(Mutation::Death(player_id, killer))
             .await?;
+        self.broadcast_info(InfoMessage::Basic(match killer {
+            Some(killer) if killer != player_id => {
+                format!(
+                    "{} was killed by {}",
+                    self.name(player_id),
+                    self.name(killer)
+                )
+            }
+            _ => format!("{} died", self.name(player_id)),
+        }))
+        .await?;
+
+        if let Some(killer) = killer.filter(|&k| k != player_id) {
+            for equipment in self.state.player(player_id).equipment().to_vec() {
+                self.mutate_state(Mutation::TransferEquipment(equipment, player_id, killer))
+                    .await?;
+            }
+        }
+
+        self.dispatch(Event::Death {
+            player: player_id,
+            killer,
+        })
+        .await?;
         Ok(())
     }
 
+    pub(crate) async fn heal(
+        &mut self,
+        source: Option<PlayerId>,
+        target: PlayerId,
+        amount: usize,
+    ) -> Result<()> {
+        let event = Event::Heal {
+            source,
+            target,
+            amount,
+        };
+        if let Some(Event::Heal { target, amount, .. }) = self.dispatch(event).await? {
+            self.mutate_state(Mutation::HealPlayer(target, amount))
+                .await?;
+        }
+        Ok(())
+    }
+
+    /// Moves a player and returns where it ended up, or `None` if the move
+    /// was cancelled.
+    pub(crate) async fn move_player(
+        &mut self,
+        player: PlayerId,
+        location: LocationId,
+    ) -> Result<Option<LocationId>> {
+        let event = Event::Move { player, location };
+        let Some(Event::Move { player, location }) = self.dispatch(event).await? else {
+            return Ok(None);
+        };
+        self.mutate_state(Mutation::Move(player, location)).await?;
+        Ok(Some(location))
+    }
+
     pub(crate) async fn mutate_state(&mut self, mutation: Mutation) -> Result<()> {
         self.state.mutate(mutation);
         self.message_channel
             .send(Command::StateMutation(mutation))
             .await?;
-        // TODO: check victory condition
+        if self.winners.is_none() {
+            self.check_victory();
+        }
         Ok(())
     }
 
+    /// Ends the game once a player fulfilled its victory condition. Players
+    /// whose condition is only checked at the end of the game, like
+    /// surviving until then, win alongside.
+    fn check_victory(&mut self) {
+        let game_logic = &*self;
+        let has_won = |player: &shared::state::Player<'_>, game_over| {
+            let character = player
+                .character()
+                .expect("The engine knows every character");
+            Characters::from_id(character.id()).has_won(player.id(), game_logic, game_over)
+        };
+        if !self.state.players().any(|p| has_won(&p, false)) {
+            return;
+        }
+        let winners = self
+            .state
+            .players()
+            .filter(|p| has_won(p, true))
+            .map(|p| p.id())
+            .collect();
+        self.winners = Some(winners);
+    }
+
+    pub(crate) async fn send_info(
+        &mut self,
+        destination: Vec<PlayerId>,
+        message: InfoMessage,
+    ) -> Result<()> {
+        self.message_channel
+            .send(Command::Info {
+                destination,
+                payload: message,
+            })
+            .await
+    }
+
     pub(crate) async fn broadcast_info(&mut self, message: InfoMessage) -> Result<()> {
         self.message_channel
             .send(Command::Info {
@@ -220,32 +711,322 @@ impl GameLogic {
 }
 
 #[derive(Debug)]
-pub(crate) struct MessageChannel(mpsc::Sender<Command>);
+pub(crate) struct MessageChannel {
+    sender: mpsc::Sender<Command>,
+    next_request_id: RequestId,
+}
 
 impl MessageChannel {
+    fn new(sender: mpsc::Sender<Command>) -> Self {
+        Self {
+            sender,
+            next_request_id: RequestId::new(0),
+        }
+    }
+
+    /// Requests an action from a player and returns the value mapped to the
+    /// chosen action. Out of range answers are not expected (`back` validates
+    /// them) but are handled by asking again rather than panicking.
     pub(crate) async fn request_action_map<T>(
         &mut self,
         from_player: PlayerId,
+        prompt: Prompt,
         choices: impl IntoIterator<Item = (Action, T)>,
     ) -> Result<T>
     where
         T: Copy + Send,
     {
+        let mut res = self
+            .request_actions_map([(from_player, prompt, choices.into_iter().collect())])
+            .await?;
+        Ok(res.remove(0))
+    }
+
+    /// Requests an action from several players at once, all of them being
+    /// able to answer simultaneously. Values are returned in request order.
+    pub(crate) async fn request_actions_map<T>(
+        &mut self,
+        requests: impl IntoIterator<Item = (PlayerId, Prompt, Vec<(Action, T)>)>,
+    ) -> Result<Vec<T>>
+    where
+        T: Copy + Send,
+    {
+        let requests = requests
+            .into_iter()
+            .map(|(player, prompt, choices)| {
+                let (choices, values): (Vec<_>, Vec<_>) = choices.into_iter().unzip();
+                (player, prompt, choices, values)
+            })
+            .collect::<Vec<_>>();
+
+        let mut responses = Vec::with_capacity(requests.len());
+        for (player, prompt, choices, _) in &requests {
+            responses.push(self.send_request(*player, prompt, choices).await?);
+        }
+
+        let mut res = Vec::with_capacity(requests.len());
+        for ((player, prompt, choices, values), mut response) in requests.iter().zip(responses) {
+            loop {
+                if let Some(&v) = values.get(response.await?) {
+                    res.push(v);
+                    break;
+                }
+                response = self.send_request(*player, prompt, choices).await?;
+            }
+        }
+        Ok(res)
+    }
+
+    async fn send_request(
+        &mut self,
+        player: PlayerId,
+        prompt: &Prompt,
+        choices: &[Action],
+    ) -> Result<oneshot::Receiver<usize>> {
+        let id = self.next_request_id;
+        self.next_request_id = id.next();
+
         let (snd, rcv) = oneshot::channel();
-        let (choices, res): (_, Vec<_>) = choices.into_iter().unzip();
-        self.0
+        self.sender
             .send(Command::ActionRequest {
-                player: from_player,
-                choices,
+                id,
+                player,
+                prompt: prompt.clone(),
+                choices: choices.to_vec(),
                 response: snd,
             })
             .await?;
-        let r = rcv.await?;
-        Ok(res[r])
+        Ok(rcv)
     }
 
     pub(crate) async fn send(&mut self, message: Command) -> Result<()> {
-        self.0.send(message).await?;
+        self.sender.send(message).await?;
         Ok(())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use test_utils::names;
+
+    #[tokio::test]
+    async fn out_of_range_answer_is_requested_again() {
+        let (tx, mut rx) = mpsc::channel(1);
+        let mut channel = MessageChannel::new(tx);
+
+        let answers = tokio::spawn(async move {
+            let mut ids = vec![];
+            for choice in [5, 1] {
+                match rx.recv().await.unwrap() {
+                    Command::ActionRequest { id, response, .. } => {
+                        ids.push(id);
+                        response.send(choice).unwrap();
+                    }
+                    c => panic!("Unexpected command {:?}", c),
+                }
+            }
+            ids
+        });
+
+        let res = channel
+            .request_action_map(
+                PlayerId::new(0),
+                Prompt::new(PromptKind::AttackTarget),
+                [(Action::Skip, 'a'), (Action::Skip, 'b')],
+            )
+            .await
+            .unwrap();
+        assert_eq!(res, 'b');
+
+        let ids = answers.await.unwrap();
+        assert_ne!(ids[0], ids[1]);
+    }
+
+    #[tokio::test]
+    async fn concurrent_requests_are_all_sent_before_waiting() {
+        let (tx, mut rx) = mpsc::channel(2);
+        let mut channel = MessageChannel::new(tx);
+
+        let answers = tokio::spawn(async move {
+            let mut responses = vec![];
+            for _ in 0..2 {
+                match rx.recv().await.unwrap() {
+                    Command::ActionRequest {
+                        player, response, ..
+                    } => responses.push((player, response)),
+                    c => panic!("Unexpected command {:?}", c),
+                }
+            }
+            // Answer in reverse order
+            while let Some((player, response)) = responses.pop() {
+                response
+                    .send(if player == PlayerId::new(0) { 0 } else { 1 })
+                    .unwrap();
+            }
+        });
+
+        let res = channel
+            .request_actions_map([
+                (
+                    PlayerId::new(0),
+                    Prompt::new(PromptKind::HermitAnswer),
+                    vec![(Action::Skip, 'a'), (Action::Skip, 'b')],
+                ),
+                (
+                    PlayerId::new(1),
+                    Prompt::new(PromptKind::HermitAnswer),
+                    vec![(Action::Skip, 'c'), (Action::Skip, 'd')],
+                ),
+            ])
+            .await
+            .unwrap();
+        assert_eq!(res, ['a', 'd']);
+        answers.await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn dead_players_are_never_prompted() {
+        for count in PLAYER_COUNTS {
+            let (tx, mut rx) = mpsc::channel(16);
+            let packs = vec![Pack::Base, Pack::Expansion];
+            let mut game_logic = GameLogic::new(names(count), packs, Board::base(), tx).unwrap();
+            let answers = tokio::spawn(async move {
+                let mut dead = vec![];
+                while let Some(command) = rx.recv().await {
+                    match command {
+                        Command::StateMutation(Mutation::Death(player, _)) => dead.push(player),
+                        Command::ActionRequest {
+                            player,
+                            choices,
+                            response,
+                            ..
+                        } => {
+                            assert!(!dead.contains(&player), "{:?} is dead", player);
+                            response.send(thread_rng().gen_range(0..choices.len())).ok();
+                        }
+                        _ => {}
+                    }
+                }
+            });
+
+            game_logic.run().await.unwrap();
+            drop(game_logic);
+            answers.await.unwrap();
+        }
+    }
+
+    #[test]
+    fn new_rejects_invalid_player_counts() {
+        for count in [0, 3, 9] {
+            let (tx, _rx) = mpsc::channel(16);
+            assert!(GameLogic::new(names(count), vec![Pack::Base], Board::base(), tx).is_err());
+        }
+    }
+
+    #[tokio::test]
+    async fn lethal_damage_kills_and_reveals() {
+        let (tx, _rx) = mpsc::channel(16);
+        let mut game_logic = GameLogic::new(names(4), vec![Pack::Base], Board::base(), tx).unwrap();
+        let (target, killer) = (PlayerId::new(0), PlayerId::new(1));
+
+        game_logic.damage(Some(killer), target, 1).await.unwrap();
+        assert!(game_logic.state.player(target).is_alive());
+
+        game_logic.damage(Some(killer), target, 100).await.unwrap();
+        let target = game_logic.state.player(target);
+        assert!(!target.is_alive());
+        assert!(target.revealed());
+        assert_eq!(target.killer(), Some(killer));
+    }
+
+    #[tokio::test]
+    async fn statuses_skip_turns_and_expire() {
+        let (tx, _rx) = mpsc::channel(64);
+        let mut game_logic = GameLogic::with_characters(
+            Characters::generate(3, &[Pack::Base]).unwrap(),
+            names(3),
+            vec![Pack::Base],
+            PlayerId::new(0),
+            Board::base(),
+            tx,
+        );
+        let [first, second, third] = [0, 1, 2].map(PlayerId::new);
+        game_logic
+            .apply_status(second, Status::SkipTurn, 1)
+            .await
+            .unwrap();
+        game_logic
+            .apply_status(third, Status::Immune, 2)
+            .await
+            .unwrap();
+
+        game_logic.next_player().await.unwrap();
+        assert_eq!(game_logic.state.current_player().id(), third);
+        assert!(!game_logic.state.player(second).has_status(Status::SkipTurn));
+        assert_eq!(
+            game_logic.state.player(third).statuses(),
+            [StatusEffect::new(Status::Immune, 1)]
+        );
+        assert_eq!(game_logic.damage(None, third, 3).await.unwrap(), 0);
+
+        game_logic.next_player().await.unwrap();
+        assert_eq!(game_logic.state.current_player().id(), first);
+        game_logic.next_player().await.unwrap();
+        game_logic.next_player().await.unwrap();
+        assert!(game_logic.state.player(third).statuses().is_empty());
+        assert_eq!(game_logic.damage(None, third, 3).await.unwrap(), 3);
+    }
+
+    #[tokio::test]
+    async fn attack_anyone_reaches_players_outside_the_group() {
+        let offered = Arc::new(std::sync::Mutex::new(Vec::new()));
+        let mut game_logic = test_utils::game_with(&["Gregor", "Emi", "Liche"], {
+            let offered = Arc::clone(&offered);
+            move |_, _, choices| {
+                offered.lock().unwrap().push(choices.to_vec());
+                test_utils::choose(choices, Action::Skip)
+            }
+        });
+        let [attacker, near, far] = [0, 1, 2].map(PlayerId::new);
+        let location = game_logic.state.locations().iter().next().unwrap().id();
+        let in_group = game_logic
+            .state
+            .locations()
+            .in_group_iter(location)
+            .map(|l| l.id())
+            .collect::<Vec<_>>();
+        let out_group = game_logic
+            .state
+            .locations()
+            .out_group_iter(location)
+            .next()
+            .unwrap()
+            .id();
+        for (player, location) in [(attacker, location), (near, in_group[1]), (far, out_group)] {
+            game_logic
+                .mutate_state(Mutation::Move(player, location))
+                .await
+                .unwrap();
+        }
+
+        game_logic.attack().await.unwrap();
+        game_logic
+            .apply_status(attacker, Status::AttackAnyone, 1)
+            .await
+            .unwrap();
+        game_logic.attack().await.unwrap();
+
+        assert_eq!(
+            *offered.lock().unwrap(),
+            [
+                vec![Action::DamagePlayer(near, None), Action::Skip],
+                vec![
+                    Action::DamagePlayer(near, None),
+                    Action::DamagePlayer(far, None),
+                    Action::Skip
+                ],
+            ]
+        );
+    }
+}
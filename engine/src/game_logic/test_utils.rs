@@ -0,0 +1,99 @@
+use shared::{
+    state::{Mutation, Pack},
+    Action, LocationId, PlayerId, Prompt,
+};
+use tokio::sync::mpsc;
+
+use super::{Command, GameLogic};
+use crate::{characters::Characters, Board, Event, EventHandler, Flow, LocationEffect};
+
+/// Game on the base board where the `i`th player plays the `i`th named
+/// character, and where the first player starts. Every action request is
+/// answered with `answer`, other commands being ignored.
+pub(crate) fn game_with(
+    characters: &[&str],
+    mut answer: impl FnMut(PlayerId, &Prompt, &[Action]) -> usize + Send + 'static,
+) -> GameLogic {
+    let states = Characters::states();
+    let characters = characters
+        .iter()
+        .map(|name| {
+            states
+                .iter()
+                .find(|c| c.name() == *name)
+                .expect("Unknown character")
+                .id()
+        })
+        .collect::<Vec<_>>();
+    let characters_count = characters.len();
+    let (tx, mut rx) = mpsc::channel(16);
+    tokio::spawn(async move {
+        while let Some(command) = rx.recv().await {
+            if let Command::ActionRequest {
+                player,
+                prompt,
+                choices,
+                response,
+                ..
+            } = command
+            {
+                response.send(answer(player, &prompt, &choices)).ok();
+            }
+        }
+    });
+    GameLogic::with_characters(
+        characters,
+        names(characters_count),
+        vec![Pack::Base, Pack::Expansion],
+        PlayerId::new(0),
+        Board::base(),
+        tx,
+    )
+}
+
+/// Answer for games where no action request is expected. It fails the
+/// request, and with it the action of the game which sent it.
+pub(crate) fn never_asked(player: PlayerId, prompt: &Prompt, _choices: &[Action]) -> usize {
+    panic!("{:?} was asked to {:?}", player, prompt.kind)
+}
+
+pub(crate) fn names(count: usize) -> Vec<String> {
+    (0..count).map(|i| format!("Player {}", i)).collect()
+}
+
+/// Index of `action` in `choices`, or of the first choice if absent.
+pub(crate) fn choose(choices: &[Action], action: Action) -> usize {
+    choices.iter().position(|&a| a == action).unwrap_or(0)
+}
+
+pub(crate) async fn reveal(game_logic: &mut GameLogic, player_id: PlayerId) {
+    let character = game_logic.state.player(player_id).character().unwrap().id();
+    game_logic
+        .mutate_state(Mutation::RevealPlayer(player_id, character))
+        .await
+        .unwrap();
+}
+
+/// Location with the given effect, whatever its name on the board.
+pub(crate) fn location_with(game_logic: &GameLogic, effect: LocationEffect) -> LocationId {
+    game_logic
+        .state
+        .locations()
+        .iter()
+        .map(|l| l.id())
+        .find(|&l| *game_logic.location_effect(l) == effect)
+        .expect("The board has a location with this effect")
+}
+
+/// Makes every attack hit for the given damage, whatever the roll.
+pub(crate) struct FixedDamage(pub(crate) usize);
+
+#[async_trait::async_trait]
+impl EventHandler for FixedDamage {
+    async fn handle(&self, _game_logic: &mut GameLogic, event: &mut Event) -> anyhow::Result<Flow> {
+        if let Event::BeforeAttack { damage, .. } = event {
+            *damage = self.0;
+        }
+        Ok(Flow::Continue)
+    }
+}
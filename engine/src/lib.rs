@@ -1,5 +1,9 @@
 mod characters;
+mod events;
 mod game_logic;
 mod locations;
 
-pub use game_logic::{Command, GameLogic};
+pub use events::{Event, EventHandler, Flow, HandlerId};
+pub use game_logic::{Command, GameLogic, PLAYER_COUNTS};
+pub use locations::{Board, LocationDefinition, LocationEffect};
+pub use shared::{state, Action, InfoMessage, PlayerId, Prompt, RequestId};
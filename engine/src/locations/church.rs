@@ -1,3 +1,4 @@
+use anyhow::Result;
 use shared::PlayerId;
 
 use crate::GameLogic;
@@ -9,13 +10,7 @@ pub(crate) struct Church;
 
 #[async_trait::async_trait]
 impl LocationBehavior for Church {
-    fn name() -> &'static str {
-        "Church"
+    async fn handle(&self, _game_logic: &mut GameLogic, _player_id: PlayerId) -> Result<()> {
+        Ok(())
     }
-
-    fn dice_numbers() -> Vec<usize> {
-        vec![6]
-    }
-
-    async fn handle(&self, game_logic: &mut GameLogic, player_id: PlayerId) {}
 }
@@ -1,4 +1,5 @@
-use shared::PlayerId;
+use anyhow::Result;
+use shared::{state::Mutation, Action, InfoMessage, PlayerId, Prompt, PromptKind, PromptSource};
 
 use crate::GameLogic;
 
@@ -9,13 +10,101 @@ pub(crate) struct ErstwhileAltar;
 
 #[async_trait::async_trait]
 impl LocationBehavior for ErstwhileAltar {
-    fn name() -> &'static str {
-        "Erstwhile Altar"
+    async fn handle(&self, game_logic: &mut GameLogic, player_id: PlayerId) -> Result<()> {
+        let location_id = game_logic
+            .state
+            .player(player_id)
+            .location()
+            .expect("Player is in the Erstwhile Altar")
+            .id();
+        let choices = game_logic
+            .state
+            .players()
+            .filter(|p| p.is_alive() && p.id() != player_id)
+            .flat_map(|p| {
+                let owner = p.id();
+                p.equipment()
+                    .iter()
+                    .map(move |&e| (Action::Equipment(e), Some((owner, e))))
+            })
+            .collect::<Vec<_>>();
+        if choices.is_empty() {
+            return Ok(());
+        }
+        let Some((owner, equipment)) = game_logic
+            .message_channel
+            .request_action_map(
+                player_id,
+                Prompt::new(PromptKind::LocationEffect)
+                    .with_source(PromptSource::Location(location_id)),
+                std::iter::once((Action::Skip, None)).chain(choices),
+            )
+            .await?
+        else {
+            return Ok(());
+        };
+        game_logic
+            .mutate_state(Mutation::TransferEquipment(equipment, owner, player_id))
+            .await?;
+        game_logic
+            .broadcast_info(InfoMessage::Basic(format!(
+                "{} takes {} from {}",
+                game_logic.name(player_id),
+                equipment.name(),
+                game_logic.name(owner)
+            )))
+            .await
     }
+}
 
-    fn dice_numbers() -> Vec<usize> {
-        vec![10]
-    }
+#[cfg(test)]
+mod tests {
+    use shared::{
+        state::{EquipmentId, Mutation},
+        Action, PlayerId,
+    };
+
+    use super::ErstwhileAltar;
+    use crate::{
+        game_logic::test_utils::{choose, game_with, location_with},
+        locations::LocationBehavior,
+        LocationEffect,
+    };
 
-    async fn handle(&self, game_logic: &mut GameLogic, player_id: PlayerId) {}
+    #[tokio::test]
+    async fn steals_equipment_from_other_players() {
+        let stolen = EquipmentId::new(1);
+        let mut game_logic = game_with(&["Gregor", "Allie", "Emi"], move |_, _, choices| {
+            choose(choices, Action::Equipment(stolen))
+        });
+        let (player, owner) = (PlayerId::new(0), PlayerId::new(2));
+        let altar = location_with(&game_logic, LocationEffect::StealEquipment);
+        game_logic
+            .mutate_state(Mutation::Move(player, altar))
+            .await
+            .unwrap();
+
+        // Nobody has equipment to steal
+        ErstwhileAltar
+            .handle(&mut game_logic, player)
+            .await
+            .unwrap();
+        assert!(game_logic.state.player(player).equipment().is_empty());
+
+        for equipment in [EquipmentId::new(0), stolen] {
+            game_logic
+                .mutate_state(Mutation::GiveEquipment(owner, equipment))
+                .await
+                .unwrap();
+        }
+        ErstwhileAltar
+            .handle(&mut game_logic, player)
+            .await
+            .unwrap();
+        assert_eq!(game_logic.state.player(player).equipment(), [stolen]);
+        assert_eq!(
+            game_logic.state.player(owner).equipment(),
+            [EquipmentId::new(0)]
+        );
+    }
 }
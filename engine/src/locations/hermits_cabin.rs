@@ -1,21 +1,249 @@
-use shared::PlayerId;
+use anyhow::Result;
+use rand::seq::SliceRandom;
+use shared::{state::Faction, Action, InfoMessage, PlayerId, Prompt, PromptKind, PromptSource};
 
-use crate::GameLogic;
+use crate::{characters::Characters, events::Event, GameLogic};
 
 use super::LocationBehavior;
 
 #[derive(Debug)]
 pub(crate) struct HermitsCabin;
 
+#[derive(Debug, Clone, Copy)]
+enum HermitEffect {
+    Damage(usize),
+    Heal(usize),
+}
+
+/// Card given secretly to another player, whose effect applies if the
+/// player's character belongs to one of the listed factions.
+#[derive(Debug)]
+struct HermitCard {
+    name: &'static str,
+    factions: &'static [Faction],
+    effect: HermitEffect,
+}
+
+const HERMIT_CARDS: &[HermitCard] = &[
+    HermitCard {
+        name: "Aid",
+        factions: &[Faction::Hunter],
+        effect: HermitEffect::Heal(1),
+    },
+    HermitCard {
+        name: "Anger",
+        factions: &[Faction::Hunter, Faction::Shadow],
+        effect: HermitEffect::Damage(1),
+    },
+    HermitCard {
+        name: "Exorcism",
+        factions: &[Faction::Shadow],
+        effect: HermitEffect::Damage(2),
+    },
+    HermitCard {
+        name: "Fiddle",
+        factions: &[Faction::Neutral, Faction::Shadow],
+        effect: HermitEffect::Damage(1),
+    },
+    HermitCard {
+        name: "Huddle",
+        factions: &[Faction::Shadow],
+        effect: HermitEffect::Heal(1),
+    },
+    HermitCard {
+        name: "Nurturance",
+        factions: &[Faction::Neutral],
+        effect: HermitEffect::Heal(1),
+    },
+    HermitCard {
+        name: "Slap",
+        factions: &[Faction::Hunter],
+        effect: HermitEffect::Damage(1),
+    },
+    HermitCard {
+        name: "Spell",
+        factions: &[Faction::Shadow],
+        effect: HermitEffect::Damage(1),
+    },
+];
+
+impl HermitCard {
+    fn text(&self) -> String {
+        let effect = match self.effect {
+            HermitEffect::Damage(n) => format!("take {} damage", n),
+            HermitEffect::Heal(n) => format!("heal {} damage", n),
+        };
+        format!(
+            "{}: I bet you're one of {:?}. If so, {}.",
+            self.name, self.factions, effect
+        )
+    }
+}
+
 #[async_trait::async_trait]
 impl LocationBehavior for HermitsCabin {
-    fn name() -> &'static str {
-        "Hermits Cabin"
+    async fn handle(&self, game_logic: &mut GameLogic, player_id: PlayerId) -> Result<()> {
+        let card = HERMIT_CARDS
+            .choose(&mut rand::thread_rng())
+            .expect("Hermit deck is not empty");
+        let drawn = Event::CardDrawn {
+            player: player_id,
+            card: card.name.to_owned(),
+        };
+        if game_logic.dispatch(drawn).await?.is_none() {
+            return Ok(());
+        }
+        let prompt = Prompt::new(PromptKind::CardTarget)
+            .with_source(PromptSource::Card(card.name.to_owned()));
+        let targets = game_logic
+            .state
+            .players()
+            .filter(|p| p.is_alive() && p.id() != player_id)
+            .map(|p| (Action::ChoosePlayer(p.id()), Some(p.id())))
+            .collect::<Vec<_>>();
+        let target = game_logic
+            .message_channel
+            .request_action_map(
+                player_id,
+                prompt,
+                std::iter::once((Action::Skip, None)).chain(targets),
+            )
+            .await?;
+        let Some(target) = target else {
+            return Ok(());
+        };
+
+        game_logic
+            .send_info(vec![player_id, target], InfoMessage::Basic(card.text()))
+            .await?;
+        let applies = hermit_answer(game_logic, target, card).await?;
+        game_logic
+            .broadcast_info(InfoMessage::Basic(format!(
+                "{} says {} applies to them: {}",
+                game_logic.name(target),
+                card.name,
+                applies
+            )))
+            .await?;
+        if !applies {
+            return Ok(());
+        }
+        match card.effect {
+            HermitEffect::Damage(n) => {
+                game_logic.damage(Some(player_id), target, n).await?;
+            }
+            HermitEffect::Heal(n) => game_logic.heal(Some(player_id), target, n).await?,
+        }
+        Ok(())
     }
+}
 
-    fn dice_numbers() -> Vec<usize> {
-        vec![2, 3]
+/// Asks the target of a Hermit card whether it applies. Only characters
+/// allowed to lie are offered the false answer.
+async fn hermit_answer(
+    game_logic: &mut GameLogic,
+    target: PlayerId,
+    card: &HermitCard,
+) -> Result<bool> {
+    let character = game_logic
+        .state
+        .player(target)
+        .character()
+        .expect("The engine knows every character");
+    let truth = card.factions.contains(&character.faction());
+    let can_lie = Characters::from_id(character.id()).can_lie();
+    let answers = if can_lie {
+        vec![truth, !truth]
+    } else {
+        vec![truth]
+    };
+    game_logic
+        .message_channel
+        .request_action_map(
+            target,
+            Prompt::new(PromptKind::HermitAnswer)
+                .with_source(PromptSource::Card(card.name.to_owned())),
+            answers.into_iter().map(|a| (Action::HermitAnswer(a), a)),
+        )
+        .await
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::{Arc, Mutex};
+
+    use shared::{Action, PlayerId};
+
+    use super::{hermit_answer, HermitsCabin, HERMIT_CARDS};
+    use crate::{
+        events::{Event, EventHandler, Flow},
+        game_logic::test_utils::{game_with, never_asked},
+        locations::LocationBehavior,
+        GameLogic,
+    };
+
+    /// Records drawn cards and discards them.
+    #[derive(Default)]
+    struct Discard(Mutex<Vec<(PlayerId, String)>>);
+
+    #[async_trait::async_trait]
+    impl EventHandler for Discard {
+        async fn handle(
+            &self,
+            _game_logic: &mut GameLogic,
+            event: &mut Event,
+        ) -> anyhow::Result<Flow> {
+            Ok(match event {
+                Event::CardDrawn { player, card } => {
+                    self.0.lock().unwrap().push((*player, card.clone()));
+                    Flow::Cancel
+                }
+                _ => Flow::Continue,
+            })
+        }
     }
 
-    async fn handle(&self, game_logic: &mut GameLogic, player_id: PlayerId) {}
+    #[tokio::test]
+    async fn drawing_a_hermit_card_can_be_cancelled() {
+        // Discarded cards are not answered
+        let mut game_logic = game_with(&["Metamorphe", "Gregor"], never_asked);
+        let discard = Arc::new(Discard::default());
+        game_logic.register_handler(0, Arc::clone(&discard) as Arc<dyn EventHandler>);
+
+        HermitsCabin
+            .handle(&mut game_logic, PlayerId::new(0))
+            .await
+            .unwrap();
+
+        let drawn = discard.0.lock().unwrap();
+        assert_eq!(drawn.len(), 1);
+        assert_eq!(drawn[0].0, PlayerId::new(0));
+        assert!(HERMIT_CARDS.iter().any(|c| c.name == drawn[0].1));
+    }
+
+    #[tokio::test]
+    async fn only_metamorphe_can_lie() {
+        let offered = Arc::new(Mutex::new(Vec::new()));
+        let mut game_logic = game_with(&["Metamorphe", "Gregor"], {
+            let offered = Arc::clone(&offered);
+            move |_, _, choices| {
+                offered.lock().unwrap().push(choices.to_vec());
+                choices.len() - 1
+            }
+        });
+        // Exorcism only applies to Shadows
+        let card = HERMIT_CARDS.iter().find(|c| c.name == "Exorcism").unwrap();
+
+        let metamorphe = hermit_answer(&mut game_logic, PlayerId::new(0), card).await;
+        let gregor = hermit_answer(&mut game_logic, PlayerId::new(1), card).await;
+        assert!(!metamorphe.unwrap());
+        assert!(!gregor.unwrap());
+        assert_eq!(
+            *offered.lock().unwrap(),
+            [
+                vec![Action::HermitAnswer(true), Action::HermitAnswer(false)],
+                vec![Action::HermitAnswer(false)],
+            ]
+        );
+    }
 }
@@ -5,25 +5,211 @@ mod hermits_cabin;
 mod underworld_gate;
 mod weird_woods;
 
+use std::{path::Path, sync::Arc};
+
+use anyhow::{bail, Context, Result};
 use shared::PlayerId;
 
 use crate::GameLogic;
 
-pub(crate) fn location_behaviors() -> [&'static dyn LocationBehavior; 6] {
-    [
-        &cemetry::Cemetry,
-        &church::Church,
-        &erstwhile_altar::ErstwhileAltar,
-        &hermits_cabin::HermitsCabin,
-        &underworld_gate::UnderworldGate,
-        &weird_woods::WeirdWoods,
-    ]
+/// Effect of a location, binding its definition to one of the behaviors
+/// implemented in this module.
+#[derive(Debug, Clone, PartialEq, Eq, serde::Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum LocationEffect {
+    HermitCard,
+    AnyCard,
+    WhiteCard,
+    BlackCard,
+    DamageOrHeal { damage: usize, heal: usize },
+    StealEquipment,
+}
+
+impl LocationEffect {
+    pub(crate) fn behavior(&self) -> Arc<dyn LocationBehavior> {
+        match *self {
+            LocationEffect::HermitCard => Arc::new(hermits_cabin::HermitsCabin),
+            LocationEffect::AnyCard => Arc::new(underworld_gate::UnderworldGate),
+            LocationEffect::WhiteCard => Arc::new(church::Church),
+            LocationEffect::BlackCard => Arc::new(cemetry::Cemetry),
+            LocationEffect::DamageOrHeal { damage, heal } => {
+                Arc::new(weird_woods::WeirdWoods { damage, heal })
+            }
+            LocationEffect::StealEquipment => Arc::new(erstwhile_altar::ErstwhileAltar),
+        }
+    }
+}
+
+#[derive(Debug, Clone, serde::Deserialize)]
+pub struct LocationDefinition {
+    pub name: String,
+    pub dice_numbers: Vec<usize>,
+    #[serde(default)]
+    pub description: String,
+    pub effect: LocationEffect,
+}
+
+/// Set of locations a game is played with, and how they are grouped once
+/// laid out on the table.
+#[derive(Debug, Clone)]
+pub struct Board {
+    pub(crate) locations: Vec<LocationDefinition>,
+    pub(crate) group_sizes: Vec<usize>,
+}
+
+impl Board {
+    /// The six locations of the base game, in three groups of two.
+    pub fn base() -> Self {
+        Self::from_toml(include_str!("base.toml")).expect("Base board is valid")
+    }
+
+    /// Board defined in a TOML file, see `base.toml` for the format.
+    pub fn load(path: impl AsRef<Path>) -> Result<Self> {
+        let path = path.as_ref();
+        let content = std::fs::read_to_string(path)
+            .with_context(|| format!("Cannot read board file {}", path.display()))?;
+        Self::from_toml(&content).with_context(|| format!("Invalid board file {}", path.display()))
+    }
+
+    pub fn from_toml(content: &str) -> Result<Self> {
+        #[derive(serde::Deserialize)]
+        struct BoardFile {
+            locations: Vec<LocationDefinition>,
+            group_sizes: Vec<usize>,
+        }
+
+        let file: BoardFile = toml::from_str(content)?;
+        Self::new(file.locations, file.group_sizes)
+    }
+
+    /// Board made of the named locations of the base game, grouped according
+    /// to `group_sizes`.
+    pub fn custom(names: &[&str], group_sizes: Vec<usize>) -> Result<Self> {
+        let available = Self::base().locations;
+        let locations = names
+            .iter()
+            .map(|name| match available.iter().find(|l| l.name == *name) {
+                Some(l) => Ok(l.clone()),
+                None => bail!("Unknown location {:?}", name),
+            })
+            .collect::<Result<Vec<_>>>()?;
+        Self::new(locations, group_sizes)
+    }
+
+    pub fn new(locations: Vec<LocationDefinition>, group_sizes: Vec<usize>) -> Result<Self> {
+        for (i, l) in locations.iter().enumerate() {
+            if locations[..i].iter().any(|o| o.name == l.name) {
+                bail!("Location {:?} is used twice", l.name);
+            }
+            if l.dice_numbers.is_empty() {
+                bail!("Location {:?} has no dice numbers", l.name);
+            }
+            if let Some(n) = l
+                .dice_numbers
+                .iter()
+                .find(|&&n| !(2..=12).contains(&n) || n == 7)
+            {
+                bail!("Location {:?} uses invalid dice number {}", l.name, n);
+            }
+            if let Some(o) = locations[..i]
+                .iter()
+                .find(|o| o.dice_numbers.iter().any(|n| l.dice_numbers.contains(n)))
+            {
+                bail!("Locations {:?} and {:?} share dice numbers", o.name, l.name);
+            }
+        }
+        if locations.len() < 2 {
+            bail!("A board needs at least two locations to move between");
+        }
+        if group_sizes.contains(&0) || group_sizes.iter().sum::<usize>() != locations.len() {
+            bail!(
+                "Group sizes {:?} do not match {} locations",
+                group_sizes,
+                locations.len()
+            );
+        }
+        Ok(Self {
+            locations,
+            group_sizes,
+        })
+    }
 }
 
 #[async_trait::async_trait]
 pub(crate) trait LocationBehavior: Send + Sync {
-    fn name(&self) -> &'static str;
-    fn dice_numbers(&self) -> Vec<usize>;
+    async fn handle(&self, game_logic: &mut GameLogic, player_id: PlayerId) -> Result<()>;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn base_board() {
+        let board = Board::base();
+        assert_eq!(board.locations.len(), 6);
+        assert_eq!(
+            board
+                .locations
+                .iter()
+                .find(|l| l.name == "Weird Woods")
+                .unwrap()
+                .effect,
+            LocationEffect::DamageOrHeal { damage: 2, heal: 1 }
+        );
+        let dice_numbers = |name| {
+            &board
+                .locations
+                .iter()
+                .find(|l| l.name == name)
+                .unwrap()
+                .dice_numbers
+        };
+        assert_eq!(dice_numbers("Church"), &[6]);
+        assert_eq!(dice_numbers("Cemetry"), &[8]);
+    }
+
+    #[test]
+    fn custom_board() {
+        let board = Board::custom(&["Church", "Cemetry", "Weird Woods"], vec![1, 2]).unwrap();
+        assert_eq!(board.locations.len(), 3);
+
+        assert!(Board::custom(&["Church", "Nowhere"], vec![2]).is_err());
+        assert!(Board::custom(&["Church", "Church"], vec![2]).is_err());
+        assert!(Board::custom(&["Church", "Cemetry"], vec![1, 2]).is_err());
+        assert!(Board::custom(&["Church"], vec![1]).is_err());
+    }
+
+    #[test]
+    fn invalid_board_file() {
+        let overlapping = r#"
+            group_sizes = [2]
+
+            [[locations]]
+            name = "A"
+            dice_numbers = [2, 3]
+            effect = { kind = "white_card" }
+
+            [[locations]]
+            name = "B"
+            dice_numbers = [3]
+            effect = { kind = "black_card" }
+        "#;
+        assert!(Board::from_toml(overlapping).is_err());
+
+        let unknown_effect = r#"
+            group_sizes = [2]
+
+            [[locations]]
+            name = "A"
+            dice_numbers = [2]
+            effect = { kind = "teleport" }
 
-    async fn handle(&self, game_logic: &mut GameLogic, player_id: PlayerId);
+            [[locations]]
+            name = "B"
+            dice_numbers = [3]
+            effect = { kind = "black_card" }
+        "#;
+        assert!(Board::from_toml(unknown_effect).is_err());
+    }
 }
@@ -1,3 +1,4 @@
+use anyhow::Result;
 use shared::PlayerId;
 
 use crate::GameLogic;
@@ -9,13 +10,7 @@ pub(crate) struct UnderworldGate;
 
 #[async_trait::async_trait]
 impl LocationBehavior for UnderworldGate {
-    fn name() -> &'static str {
-        "Underworld Gate"
+    async fn handle(&self, _game_logic: &mut GameLogic, _player_id: PlayerId) -> Result<()> {
+        Ok(())
     }
-
-    fn dice_numbers() -> Vec<usize> {
-        vec![4, 5]
-    }
-
-    async fn handle(&self, game_logic: &mut GameLogic, player_id: PlayerId) {}
 }
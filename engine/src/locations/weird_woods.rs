@@ -1,41 +1,72 @@
 use crate::game_logic::GameLogic;
+use anyhow::Result;
 use shared::PlayerId;
 
 use super::LocationBehavior;
 
 #[derive(Debug)]
-pub(crate) struct WeirdWoods;
+pub(crate) struct WeirdWoods {
+    pub(crate) damage: usize,
+    pub(crate) heal: usize,
+}
 
 #[async_trait::async_trait]
 impl LocationBehavior for WeirdWoods {
-    fn name() -> &'static str {
-        "Weird Woods"
-    }
+    async fn handle(&self, game_logic: &mut GameLogic, player_id: PlayerId) -> Result<()> {
+        use shared::{Prompt, PromptKind, PromptSource};
 
-    fn dice_numbers() -> Vec<usize> {
-        vec![9]
-    }
+        let location_id = game_logic
+            .state
+            .player(player_id)
+            .location()
+            .expect("Player is in the Weird Woods")
+            .id();
 
-    async fn handle(&self, game_logic: &mut GameLogic, player_id: PlayerId) {
-        use shared::state::Mutation;
+        #[derive(Clone, Copy)]
+        enum Effect {
+            Damage(PlayerId),
+            Heal(PlayerId),
+        }
 
-        let choices = game_logic.state.players().map(|p| {
-            (
-                shared::Action::DamagePlayer(p.id(), Some(2)),
-                Mutation::DamagePlayer(p.id(), 2),
-            )
-        });
-        let choices = choices.chain(game_logic.state.players().map(|p| {
-            (
-                shared::Action::HealPlayer(p.id(), Some(1)),
-                Mutation::HealPlayer(p.id(), 1),
-            )
-        }));
-        let mutation = game_logic
+        let choices = game_logic
+            .state
+            .players()
+            .filter(|p| p.is_alive())
+            .map(|p| {
+                (
+                    shared::Action::DamagePlayer(p.id(), Some(self.damage)),
+                    Effect::Damage(p.id()),
+                )
+            });
+        let choices = choices.chain(
+            game_logic
+                .state
+                .players()
+                .filter(|p| p.is_alive())
+                .map(|p| {
+                    (
+                        shared::Action::HealPlayer(p.id(), Some(self.heal)),
+                        Effect::Heal(p.id()),
+                    )
+                }),
+        );
+        let effect = game_logic
             .message_channel
-            .request_action_map(player_id, choices)
-            .await
-            .unwrap();
-        game_logic.mutate_state(mutation).await.unwrap();
+            .request_action_map(
+                player_id,
+                Prompt::new(PromptKind::LocationEffect)
+                    .with_source(PromptSource::Location(location_id)),
+                choices,
+            )
+            .await?;
+        match effect {
+            Effect::Damage(target) => {
+                game_logic
+                    .damage(Some(player_id), target, self.damage)
+                    .await?;
+            }
+            Effect::Heal(target) => game_logic.heal(Some(player_id), target, self.heal).await?,
+        }
+        Ok(())
     }
 }
@@ -1,53 +1,273 @@
 pub mod state;
+pub mod wire;
 
-pub use state::{CharacterId, LocationId, PlayerId};
+pub use state::{CharacterId, EquipmentId, LocationId, PlayerId};
 
-#[derive(Debug, Clone, Copy, serde::Serialize, serde::Deserialize)]
+/// Identifies an action request, so that answers can be matched to it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, serde::Serialize, serde::Deserialize)]
+pub struct RequestId(u64);
+
+impl RequestId {
+    pub fn new(id: u64) -> Self {
+        Self(id)
+    }
+
+    pub fn next(self) -> Self {
+        Self(self.0 + 1)
+    }
+}
+
+/// Why an action is being requested from a player.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+pub enum PromptKind {
+    MovementRoll,
+    LocationChoice,
+    LocationEffect,
+    AttackTarget,
+    AttackRoll,
+    /// Roll required by a card or character ability, possibly letting the
+    /// player choose which die to roll.
+    EffectRoll,
+    CardTarget,
+    HermitAnswer,
+    RevealWindow,
+    /// Whether to use the ability of the player's character, or how to use
+    /// it.
+    Ability,
+}
+
+/// What triggered an action request, when it comes from a specific game
+/// element.
+#[derive(Debug, Clone, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+pub enum PromptSource {
+    Location(LocationId),
+    Character(CharacterId),
+    Card(String),
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+pub struct Prompt {
+    pub kind: PromptKind,
+    pub source: Option<PromptSource>,
+}
+
+impl Prompt {
+    pub fn new(kind: PromptKind) -> Self {
+        Self { kind, source: None }
+    }
+
+    pub fn with_source(mut self, source: PromptSource) -> Self {
+        self.source = Some(source);
+        self
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
 pub enum Dices {
     D4,
     D6,
     Both,
 }
 
-#[derive(Debug, Clone, Copy, serde::Serialize, serde::Deserialize)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
 pub enum Action {
     Skip,
     DiceRoll(Dices),
     Location(LocationId),
     DamagePlayer(PlayerId, Option<usize>),
     HealPlayer(PlayerId, Option<usize>),
+    Reveal,
+    UseAbility,
+    ChoosePlayer(PlayerId),
+    /// Answer to a Hermit card: whether the card applies to the player.
+    HermitAnswer(bool),
+    Equipment(EquipmentId),
 }
 
-#[derive(Debug, Clone, Copy, serde::Serialize, serde::Deserialize)]
-pub struct Roll {
-    pub d4: u8,
-    pub d6: u8,
+#[derive(Debug, Clone, Copy, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+pub enum Roll {
+    D4(u8),
+    D6(u8),
+    Both { d4: u8, d6: u8 },
 }
 
 impl Roll {
+    pub fn dices(&self) -> Dices {
+        match self {
+            Roll::D4(_) => Dices::D4,
+            Roll::D6(_) => Dices::D6,
+            Roll::Both { .. } => Dices::Both,
+        }
+    }
+
+    /// Sum of both dice, or the value of the single die rolled.
     pub fn sum(&self) -> usize {
-        (self.d4 + self.d6).into()
+        match *self {
+            Roll::D4(v) | Roll::D6(v) => v.into(),
+            Roll::Both { d4, d6 } => (d4 + d6).into(),
+        }
     }
 
+    /// Difference between both dice, or the value of the single die rolled.
     pub fn diff(&self) -> usize {
-        self.d4.abs_diff(self.d6).into()
+        match *self {
+            Roll::D4(v) | Roll::D6(v) => v.into(),
+            Roll::Both { d4, d6 } => d4.abs_diff(d6).into(),
+        }
     }
 }
 
 #[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
 pub enum InfoMessage {
     Basic(String),
-    Roll { from: PlayerId, roll: Roll },
+    Roll {
+        from: PlayerId,
+        roll: Roll,
+    },
+    /// The game is over, won by the given players, dead or alive.
+    GameOver {
+        winners: Vec<PlayerId>,
+    },
+}
+
+/// A player of a room as shown in the lobby.
+#[derive(Debug, Clone, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+pub struct PlayerProfile {
+    pub id: PlayerId,
+    pub name: String,
+    pub color: Option<String>,
+    /// Whether the player is ready for the game to start.
+    pub ready: bool,
+    pub connected: bool,
+    /// Whether the seat is played by the server.
+    pub bot: bool,
+}
+
+/// Who a chat message is for.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+pub enum ChatChannel {
+    /// Everyone at the table, spectators included.
+    Table,
+    /// A single player, the sender also receiving its own message.
+    Private(PlayerId),
 }
 
+/// Version of the protocol between clients and the server, increased with
+/// every incompatible change to the messages.
+pub const PROTOCOL_VERSION: u32 = 2;
+
+/// Optional parts of the protocol, announced by both sides when connecting.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+pub enum Feature {
+    /// Seats can be reclaimed with a session token.
+    Reconnect,
+    /// Games can be followed without playing.
+    Spectators,
+    /// Seats can be played by the server.
+    Bots,
+    /// Messages after the handshake are sent as MessagePack binary frames,
+    /// see `wire`.
+    MessagePack,
+    /// Feature of a newer peer.
+    #[serde(other)]
+    Unknown,
+}
+
+/// Features supported by this version of the crate.
+pub const FEATURES: &[Feature] = &[
+    Feature::Reconnect,
+    Feature::Spectators,
+    Feature::Bots,
+    Feature::MessagePack,
+];
+
 #[derive(Debug, serde::Serialize, serde::Deserialize)]
 pub enum ToPlayer {
-    ActionRequest(Vec<Action>),
+    /// Answer to `FromPlayer::Hello`, with the features of the server. No
+    /// other message is sent before it.
+    Welcome {
+        version: u32,
+        features: Vec<Feature>,
+    },
+    ActionRequest {
+        id: RequestId,
+        prompt: Prompt,
+        choices: Vec<Action>,
+    },
     Info(InfoMessage),
     StateMutation(state::Mutation),
+    /// Full state of the game as seen by the receiving player, sent when the
+    /// game starts and whenever the player asks for a resync.
+    StateSync(Box<state::State>),
+    /// Players the table is currently waiting an answer from.
+    WaitingFor(Vec<PlayerId>),
+    Error(ErrorMessage),
+    /// Players of the room, sent whenever a player joins or leaves the
+    /// lobby. `you` is the receiving player, whose id may change until the
+    /// game starts.
+    Roster {
+        players: Vec<PlayerProfile>,
+        you: PlayerId,
+    },
+    /// Sent when joining a room. Reconnecting with it gives the player its
+    /// seat back, along with the state of the game and its pending requests.
+    SessionToken(String),
+    Chat {
+        from: PlayerId,
+        channel: ChatChannel,
+        text: String,
+    },
+}
+
+/// Errors reported to a player about a message it sent.
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+pub enum ErrorMessage {
+    /// The answered request is not, or no longer, waiting for an answer.
+    UnknownRequest(RequestId),
+    /// The choice is not one of the proposed actions. The request is sent
+    /// again right after this error.
+    InvalidChoice { request: RequestId, choice: usize },
+    /// The message could not be understood, with the reason. It is ignored.
+    InvalidMessage(String),
+    /// The client speaks another version of the protocol, and is
+    /// disconnected.
+    UnsupportedVersion { client: u32, server: u32 },
+    /// The client did not start with `FromPlayer::Hello`, and is
+    /// disconnected.
+    HelloExpected,
+    /// The chat message has more than `max` characters, and is dropped.
+    ChatTooLong { max: usize },
+    /// The player sent too many chat messages lately, this one is dropped.
+    ChatRateLimited,
+    /// The private chat message is for a player who is not in the room, and
+    /// is dropped.
+    UnknownPlayer(PlayerId),
+    /// Dead players seeing every character may not chat, so as not to tell
+    /// the living ones. The message is dropped.
+    ChatForbidden,
 }
 
 #[derive(Debug, serde::Serialize, serde::Deserialize)]
 pub enum FromPlayer {
-    ActionChoice(usize),
+    /// First message of every connection, telling the protocol version and
+    /// the features of the client.
+    Hello {
+        version: u32,
+        features: Vec<Feature>,
+    },
+    ActionChoice {
+        request: RequestId,
+        choice: usize,
+    },
+    RequestStateSync,
+    /// Tells whether the player is ready for the game to start, while in the
+    /// lobby.
+    Ready(bool),
+    Chat {
+        channel: ChatChannel,
+        text: String,
+    },
+    /// Gives up the seat of the player: it is freed while in the lobby, and
+    /// played by a bot once the game started.
+    Leave,
 }
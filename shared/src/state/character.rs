@@ -3,6 +3,18 @@ use std::ops::{Index, IndexMut};
 #[derive(Debug, Clone, Copy, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
 pub struct CharacterId(usize);
 
+impl CharacterId {
+    pub fn new(id: usize) -> Self {
+        Self(id)
+    }
+}
+
+impl From<CharacterId> for usize {
+    fn from(v: CharacterId) -> Self {
+        v.0
+    }
+}
+
 impl Index<CharacterId> for Vec<Character> {
     type Output = Character;
 
@@ -17,15 +29,75 @@ impl IndexMut<CharacterId> for Vec<Character> {
     }
 }
 
+#[derive(Debug, Clone, Copy, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+pub enum Faction {
+    Hunter,
+    Shadow,
+    Neutral,
+}
+
+/// Set of characters that can be put in play at game setup.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+pub enum Pack {
+    Base,
+    Expansion,
+}
+
+impl std::str::FromStr for Pack {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "base" => Ok(Pack::Base),
+            "expansion" => Ok(Pack::Expansion),
+            _ => Err(format!("Unknown pack {}", s)),
+        }
+    }
+}
+
 #[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
 pub struct Character {
     id: CharacterId,
     name: String,
+    faction: Faction,
     hit_points: usize,
+    pack: Pack,
 }
 
 impl Character {
+    pub fn new(
+        id: CharacterId,
+        name: String,
+        faction: Faction,
+        hit_points: usize,
+        pack: Pack,
+    ) -> Self {
+        Self {
+            id,
+            name,
+            faction,
+            hit_points,
+            pack,
+        }
+    }
+
+    pub fn id(&self) -> CharacterId {
+        self.id
+    }
+
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    pub fn faction(&self) -> Faction {
+        self.faction
+    }
+
     pub fn hit_points(&self) -> usize {
         self.hit_points
     }
+
+    pub fn pack(&self) -> Pack {
+        self.pack
+    }
 }
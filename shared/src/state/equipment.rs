@@ -0,0 +1,41 @@
+#[derive(Debug, Clone, Copy, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+pub struct EquipmentId(usize);
+
+/// Names of the equipment cards. They have no effect of their own and
+/// nothing deals them yet: they can be robbed, go to the killer of their
+/// owner and count towards the victory of Bob and David.
+const EQUIPMENT: &[&str] = &[
+    "Holy Robe",
+    "Silver Rosary",
+    "Talisman",
+    "Fortune Brooch",
+    "Mystic Compass",
+    "Spear of Longinus",
+    "Chainsaw",
+    "Butcher Knife",
+    "Rusted Broad Axe",
+    "Masamune",
+    "Machine Gun",
+    "Handgun",
+];
+
+impl EquipmentId {
+    pub fn new(id: usize) -> Self {
+        Self(id)
+    }
+
+    /// Every equipment card of the game.
+    pub fn all() -> impl Iterator<Item = EquipmentId> {
+        (0..EQUIPMENT.len()).map(EquipmentId)
+    }
+
+    pub fn name(self) -> &'static str {
+        EQUIPMENT[self.0]
+    }
+}
+
+impl From<EquipmentId> for usize {
+    fn from(v: EquipmentId) -> Self {
+        v.0
+    }
+}
@@ -11,14 +11,16 @@ impl From<LocationId> for usize {
 pub struct Location {
     id: LocationId,
     name: String,
+    description: String,
     dice_numbers: Vec<usize>,
 }
 
 impl Location {
-    pub fn new(id: usize, name: String, dice_numbers: Vec<usize>) -> Self {
+    pub fn new(id: usize, name: String, description: String, dice_numbers: Vec<usize>) -> Self {
         Self {
             id: LocationId(id),
             name,
+            description,
             dice_numbers,
         }
     }
@@ -31,29 +33,57 @@ impl Location {
         &self.name
     }
 
+    pub fn description(&self) -> &str {
+        &self.description
+    }
+
     pub fn dice_numbers(&self) -> &[usize] {
         &self.dice_numbers
     }
 }
 
+/// Locations of the board. `layout` is the order of the locations around the
+/// table, and `group_sizes` splits it into consecutive groups: the base game
+/// uses three groups of two.
 #[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
 pub struct Locations {
-    locations: [Location; 6],
-    layout: [LocationId; 6],
+    locations: Vec<Location>,
+    layout: Vec<LocationId>,
+    group_sizes: Vec<usize>,
 }
 
 impl Locations {
-    pub fn new(locations: [Location; 6], layout: [LocationId; 6]) -> Self {
+    pub fn new(locations: Vec<Location>, layout: Vec<LocationId>, group_sizes: Vec<usize>) -> Self {
         for (i, l) in locations.iter().enumerate() {
             assert!(l.id.0 == i)
         }
-        Self { locations, layout }
+        assert_eq!(layout.len(), locations.len());
+        assert!(locations.iter().all(|l| layout.contains(&l.id)));
+        assert_eq!(group_sizes.iter().sum::<usize>(), layout.len());
+        assert!(group_sizes.iter().all(|&s| s > 0));
+        Self {
+            locations,
+            layout,
+            group_sizes,
+        }
     }
 
-    pub fn from_dice_number(&self, dice_number: usize) -> &Location {
+    pub fn len(&self) -> usize {
+        self.locations.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.locations.is_empty()
+    }
+
+    pub fn try_from_dice_number(&self, dice_number: usize) -> Option<&Location> {
         self.locations
             .iter()
             .find(|l| l.dice_numbers.contains(&dice_number))
+    }
+
+    pub fn from_dice_number(&self, dice_number: usize) -> &Location {
+        self.try_from_dice_number(dice_number)
             .expect("No location corresponds to provided number")
     }
 
@@ -68,7 +98,7 @@ impl Locations {
     pub fn in_group_iter(&self, id: LocationId) -> impl Iterator<Item = &Location> + Clone + '_ {
         self.groups()
             .into_iter()
-            .filter(move |g| g.iter().any(|&i| i == id))
+            .filter(move |g| g.contains(&id))
             .flatten()
             .map(|i| &self.locations[i.0])
     }
@@ -76,7 +106,7 @@ impl Locations {
     pub fn out_group_iter(&self, id: LocationId) -> impl Iterator<Item = &Location> + Clone + '_ {
         self.groups()
             .into_iter()
-            .filter(move |g| !g.iter().any(|&i| i == id))
+            .filter(move |g| !g.contains(&id))
             .flatten()
             .map(|i| &self.locations[i.0])
     }
@@ -95,12 +125,16 @@ impl Locations {
         .map(|i| &self.locations[i.0])
     }
 
-    fn groups(&self) -> [[LocationId; 2]; 3] {
-        [
-            [self.layout[0], self.layout[1]],
-            [self.layout[2], self.layout[3]],
-            [self.layout[4], self.layout[5]],
-        ]
+    fn groups(&self) -> Vec<&[LocationId]> {
+        let mut rest = &self.layout[..];
+        self.group_sizes
+            .iter()
+            .map(|&size| {
+                let (group, r) = rest.split_at(size);
+                rest = r;
+                group
+            })
+            .collect()
     }
 }
 
@@ -108,24 +142,30 @@ impl Locations {
 mod tests {
     use super::*;
 
-    fn new_unshuffled_locations() -> Locations {
-        let layout: [LocationId; 6] = (0..6)
-            .into_iter()
+    fn new_unshuffled_locations_with_groups(group_sizes: Vec<usize>) -> Locations {
+        let layout = (0..group_sizes.iter().sum())
             .map(LocationId)
-            .collect::<Vec<_>>()
-            .try_into()
-            .unwrap();
+            .collect::<Vec<_>>();
 
         Locations::new(
-            layout.map(|id| Location {
-                id,
-                name: format!("{}", id.0),
-                dice_numbers: vec![id.0],
-            }),
+            layout
+                .iter()
+                .map(|&id| Location {
+                    id,
+                    name: format!("{}", id.0),
+                    description: String::new(),
+                    dice_numbers: vec![id.0],
+                })
+                .collect(),
             layout,
+            group_sizes,
         )
     }
 
+    fn new_unshuffled_locations() -> Locations {
+        new_unshuffled_locations_with_groups(vec![2, 2, 2])
+    }
+
     #[test]
     fn in_group_iter() {
         let locations = new_unshuffled_locations();
@@ -192,4 +232,33 @@ mod tests {
             [LocationId(4), LocationId(0)]
         );
     }
+
+    #[test]
+    fn uneven_groups() {
+        let locations = new_unshuffled_locations_with_groups(vec![3, 1, 4]);
+        assert!(locations.in_group_iter(LocationId(1)).map(|l| l.id()).eq([
+            LocationId(0),
+            LocationId(1),
+            LocationId(2)
+        ]
+        .into_iter()));
+        assert!(locations
+            .in_group_iter(LocationId(3))
+            .map(|l| l.id())
+            .eq([LocationId(3)].into_iter()));
+        assert!(locations.out_group_iter(LocationId(3)).map(|l| l.id()).eq([
+            LocationId(0),
+            LocationId(1),
+            LocationId(2),
+            LocationId(4),
+            LocationId(5),
+            LocationId(6),
+            LocationId(7)
+        ]
+        .into_iter()));
+        assert_eq!(
+            locations.adjacent(LocationId(7)).map(|l| l.id()),
+            [LocationId(6), LocationId(0)]
+        );
+    }
 }
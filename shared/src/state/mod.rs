@@ -1,12 +1,16 @@
 use std::fmt::Debug;
 
 mod character;
+mod equipment;
 mod location;
 mod player;
+mod status;
 
-pub use self::character::{Character, CharacterId};
+pub use self::character::{Character, CharacterId, Faction, Pack};
+pub use self::equipment::EquipmentId;
 pub use self::location::{Location, LocationId, Locations};
 pub use self::player::{Player, PlayerId, PlayerStorage};
+pub use self::status::{Status, StatusEffect};
 
 #[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
 pub struct State {
@@ -14,6 +18,8 @@ pub struct State {
     current_player: PlayerId,
     locations: Locations,
     characters: Vec<Character>,
+    /// Packs the characters of the game were dealt from.
+    packs: Vec<Pack>,
 }
 
 impl State {
@@ -22,41 +28,60 @@ impl State {
         locations: Locations,
         first_player: PlayerId,
         characters: Vec<Character>,
+        packs: Vec<Pack>,
     ) -> State {
         State {
             players,
             current_player: first_player,
             locations,
             characters,
+            packs,
         }
     }
 
     pub fn prepare_for_player(&self, player_id: PlayerId) -> Self {
+        self.redacted(Some(player_id))
+    }
+
+    /// State as seen by spectators, who only know revealed characters.
+    pub fn prepare_for_spectator(&self) -> Self {
+        self.redacted(None)
+    }
+
+    fn redacted(&self, viewer: Option<PlayerId>) -> Self {
         let mut res = self.clone();
         for p in &mut res.players {
-            if p.id != player_id && !p.revealed {
+            if Some(p.id) != viewer && !p.revealed {
                 p.character = None;
             }
         }
         res
     }
 
-    pub fn current_player(&self) -> Player {
+    pub fn current_player(&self) -> Player<'_> {
         Player::new(self.current_player, self)
     }
 
-    pub fn players(&self) -> impl ExactSizeIterator<Item = Player> + Clone {
+    pub fn players(&self) -> impl ExactSizeIterator<Item = Player<'_>> + Clone {
         self.players.iter().map(|p| Player::new(p.id, self))
     }
 
+    pub fn player(&self, player_id: PlayerId) -> Player<'_> {
+        Player::new(player_id, self)
+    }
+
     pub fn locations(&self) -> &Locations {
         &self.locations
     }
 
+    pub fn packs(&self) -> &[Pack] {
+        &self.packs
+    }
+
     pub fn mutate(&mut self, mutation: Mutation) {
         match mutation {
             Mutation::Move(player_id, location_id) => {
-                self.players[player_id].location = location_id;
+                self.players[player_id].location = Some(location_id);
             }
             Mutation::ChangeCurrentPlayer(player_id) => self.current_player = player_id,
             Mutation::DamagePlayer(player_id, damage) => self.players[player_id].damage += damage,
@@ -71,6 +96,29 @@ impl State {
                 }
                 player.character = Some(character_id);
             }
+            Mutation::UseAbility(player_id) => self.players[player_id].ability_used = true,
+            Mutation::Death(player_id, killer) => {
+                let player = &mut self.players[player_id];
+                player.dead = true;
+                player.killer = killer;
+            }
+            Mutation::GiveEquipment(player_id, equipment) => {
+                self.players[player_id].equipment.push(equipment);
+            }
+            Mutation::TransferEquipment(equipment, from, to) => {
+                self.players[from].equipment.retain(|&e| e != equipment);
+                self.players[to].equipment.push(equipment);
+            }
+            Mutation::ApplyStatus(player_id, effect) => {
+                let statuses = &mut self.players[player_id].statuses;
+                statuses.retain(|s| s.status() != effect.status());
+                statuses.push(effect);
+            }
+            Mutation::ExpireStatus(player_id, status) => {
+                self.players[player_id]
+                    .statuses
+                    .retain(|s| s.status() != status);
+            }
         }
     }
 }
@@ -82,4 +130,118 @@ pub enum Mutation {
     DamagePlayer(PlayerId, usize),
     HealPlayer(PlayerId, usize),
     RevealPlayer(PlayerId, CharacterId),
+    /// A player used the once per game ability of its character.
+    UseAbility(PlayerId),
+    /// A player died, killed by the given player if its death comes from
+    /// another player.
+    Death(PlayerId, Option<PlayerId>),
+    /// A player receives an equipment.
+    GiveEquipment(PlayerId, EquipmentId),
+    /// An equipment moves from the first player to the second one.
+    TransferEquipment(EquipmentId, PlayerId, PlayerId),
+    /// A player gets a status, replacing any previous effect of the same
+    /// status.
+    ApplyStatus(PlayerId, StatusEffect),
+    ExpireStatus(PlayerId, Status),
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn new_state() -> State {
+        let locations = (0..6)
+            .map(|i| Location::new(i, format!("{}", i), String::new(), vec![i]))
+            .collect::<Vec<_>>();
+        let layout = locations.iter().map(|l| l.id()).collect();
+        State::new(
+            (0..3)
+                .map(|i| {
+                    PlayerStorage::new(PlayerId::new(i), format!("{}", i), CharacterId::new(i))
+                })
+                .collect(),
+            Locations::new(locations, layout, vec![2, 2, 2]),
+            PlayerId::new(0),
+            (0..3)
+                .map(|i| {
+                    Character::new(
+                        CharacterId::new(i),
+                        format!("{}", i),
+                        Faction::Hunter,
+                        10,
+                        Pack::Base,
+                    )
+                })
+                .collect(),
+            vec![Pack::Base],
+        )
+    }
+
+    #[test]
+    fn prepare_for_player() {
+        let mut state = new_state();
+        state.mutate(Mutation::RevealPlayer(
+            PlayerId::new(2),
+            CharacterId::new(2),
+        ));
+
+        let prepared = state.prepare_for_player(PlayerId::new(0));
+        let characters = prepared
+            .players()
+            .map(|p| p.character().map(|c| c.id()))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            characters,
+            [Some(CharacterId::new(0)), None, Some(CharacterId::new(2))]
+        );
+
+        let prepared = state.prepare_for_spectator();
+        let characters = prepared
+            .players()
+            .map(|p| p.character().map(|c| c.id()))
+            .collect::<Vec<_>>();
+        assert_eq!(characters, [None, None, Some(CharacterId::new(2))]);
+    }
+
+    #[test]
+    fn death() {
+        let mut state = new_state();
+        let (killed, killer) = (PlayerId::new(1), PlayerId::new(2));
+        state.players[killed].equipment = vec![EquipmentId::new(0), EquipmentId::new(1)];
+
+        state.mutate(Mutation::Death(killed, Some(killer)));
+        state.mutate(Mutation::TransferEquipment(
+            EquipmentId::new(1),
+            killed,
+            killer,
+        ));
+
+        let player = state.player(killed);
+        assert!(!player.is_alive());
+        assert_eq!(player.killer(), Some(killer));
+        assert_eq!(player.equipment(), [EquipmentId::new(0)]);
+        assert_eq!(state.player(killer).equipment(), [EquipmentId::new(1)]);
+    }
+
+    #[test]
+    fn statuses() {
+        let mut state = new_state();
+        let player = PlayerId::new(0);
+
+        state.mutate(Mutation::ApplyStatus(
+            player,
+            StatusEffect::new(Status::Immune, 2),
+        ));
+        state.mutate(Mutation::ApplyStatus(
+            player,
+            StatusEffect::new(Status::Immune, 1),
+        ));
+        assert_eq!(
+            state.player(player).statuses(),
+            [StatusEffect::new(Status::Immune, 1)]
+        );
+
+        state.mutate(Mutation::ExpireStatus(player, Status::Immune));
+        assert!(!state.player(player).has_status(Status::Immune));
+    }
 }
@@ -2,6 +2,8 @@ use std::ops::{Index, IndexMut};
 
 use crate::{CharacterId, LocationId};
 
+use super::{EquipmentId, Status, StatusEffect};
+
 use super::{Character, Location, State};
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
@@ -30,10 +32,34 @@ impl IndexMut<PlayerId> for Vec<PlayerStorage> {
 #[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
 pub struct PlayerStorage {
     pub(crate) id: PlayerId,
+    pub(crate) name: String,
     pub(crate) damage: usize,
-    pub(crate) location: LocationId,
+    pub(crate) location: Option<LocationId>,
     pub(crate) revealed: bool,
     pub(crate) character: Option<CharacterId>,
+    pub(crate) equipment: Vec<EquipmentId>,
+    pub(crate) ability_used: bool,
+    pub(crate) dead: bool,
+    pub(crate) killer: Option<PlayerId>,
+    pub(crate) statuses: Vec<StatusEffect>,
+}
+
+impl PlayerStorage {
+    pub fn new(id: PlayerId, name: String, character: CharacterId) -> Self {
+        Self {
+            id,
+            name,
+            damage: 0,
+            location: None,
+            revealed: false,
+            character: Some(character),
+            equipment: vec![],
+            ability_used: false,
+            dead: false,
+            killer: None,
+            statuses: vec![],
+        }
+    }
 }
 
 pub struct Player<'a> {
@@ -54,27 +80,59 @@ impl<'a> Player<'a> {
         self.id
     }
 
+    pub fn name(&self) -> &'a str {
+        &self.state.players[self.id].name
+    }
+
     pub fn damage(&self) -> usize {
         self.storage().damage
     }
 
-    pub fn location(&self) -> &'a Location {
-        self.state.locations().from_id(self.storage().location)
+    pub fn location(&self) -> Option<&'a Location> {
+        self.storage()
+            .location
+            .map(|l| self.state.locations().from_id(l))
     }
 
     pub fn revealed(&self) -> bool {
         self.storage().revealed
     }
 
-    pub fn character(&self) -> Option<&Character> {
-        self.storage().character.map(|c| &self.state.characters[c])
+    pub fn character(&self) -> Option<&'a Character> {
+        let state = self.state;
+        state.players[self.id]
+            .character
+            .map(|c| &state.characters[c])
+    }
+
+    pub fn equipment(&self) -> &'a [EquipmentId] {
+        &self.state.players[self.id].equipment
+    }
+
+    pub fn ability_used(&self) -> bool {
+        self.storage().ability_used
     }
 
     pub fn is_alive(&self) -> bool {
-        if let Some(c) = self.character() {
-            self.damage() < c.hit_points()
-        } else {
-            true
-        }
+        !self.storage().dead
+    }
+
+    /// Whether the player took at least as much damage as its character can
+    /// withstand, in which case its death has to be resolved.
+    pub fn is_beyond_hit_points(&self) -> bool {
+        self.character()
+            .is_some_and(|c| self.damage() >= c.hit_points())
+    }
+
+    pub fn killer(&self) -> Option<PlayerId> {
+        self.storage().killer
+    }
+
+    pub fn statuses(&self) -> &'a [StatusEffect] {
+        &self.state.players[self.id].statuses
+    }
+
+    pub fn has_status(&self, status: Status) -> bool {
+        self.statuses().iter().any(|s| s.status() == status)
     }
 }
@@ -0,0 +1,34 @@
+/// Temporary effect on a player.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+pub enum Status {
+    /// The player takes no damage.
+    Immune,
+    /// The next turn of the player is skipped.
+    SkipTurn,
+    /// The player plays again once its turn is over.
+    ExtraTurn,
+    /// The player can attack players wherever they are.
+    AttackAnyone,
+}
+
+/// A status and the number of turns of its holder it lasts for, counting
+/// down each time one of these turns starts.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+pub struct StatusEffect {
+    status: Status,
+    turns: usize,
+}
+
+impl StatusEffect {
+    pub fn new(status: Status, turns: usize) -> Self {
+        Self { status, turns }
+    }
+
+    pub fn status(&self) -> Status {
+        self.status
+    }
+
+    pub fn turns(&self) -> usize {
+        self.turns
+    }
+}
@@ -0,0 +1,90 @@
+//! Encoding of the messages exchanged over websockets.
+//!
+//! The handshake is always in JSON text frames. Clients announcing
+//! `Feature::MessagePack` in their hello then receive MessagePack binary
+//! frames, which are much smaller for states and long spectator streams.
+//! Both sides accept either kind of frame at any time.
+
+use serde::{de::DeserializeOwned, Serialize};
+
+use crate::Feature;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Encoding {
+    Json,
+    MessagePack,
+}
+
+impl Encoding {
+    /// Encoding to use with a peer announcing `features`.
+    pub fn negotiate(features: &[Feature]) -> Self {
+        if features.contains(&Feature::MessagePack) {
+            Encoding::MessagePack
+        } else {
+            Encoding::Json
+        }
+    }
+}
+
+/// Content of a websocket frame.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Frame {
+    Text(String),
+    Binary(Vec<u8>),
+}
+
+impl Frame {
+    pub fn encode<T: Serialize>(message: &T, encoding: Encoding) -> Result<Self, String> {
+        match encoding {
+            Encoding::Json => serde_json::to_string(message)
+                .map(Frame::Text)
+                .map_err(|e| e.to_string()),
+            Encoding::MessagePack => rmp_serde::to_vec(message)
+                .map(Frame::Binary)
+                .map_err(|e| e.to_string()),
+        }
+    }
+
+    /// Decodes a message, text frames being JSON and binary ones
+    /// MessagePack.
+    pub fn decode<T: DeserializeOwned>(&self) -> Result<T, String> {
+        match self {
+            Frame::Text(text) => serde_json::from_str(text).map_err(|e| e.to_string()),
+            Frame::Binary(bytes) => rmp_serde::from_slice(bytes).map_err(|e| e.to_string()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Encoding, Frame};
+    use crate::{state::Mutation, Feature, FromPlayer, PlayerId, ToPlayer};
+
+    #[test]
+    fn round_trip() {
+        for encoding in [Encoding::Json, Encoding::MessagePack] {
+            let message = ToPlayer::StateMutation(Mutation::DamagePlayer(PlayerId::new(2), 3));
+            let frame = Frame::encode(&message, encoding).unwrap();
+            assert_eq!(
+                matches!(frame, Frame::Binary(_)),
+                encoding == Encoding::MessagePack
+            );
+            let decoded: ToPlayer = frame.decode().unwrap();
+            assert!(matches!(
+                decoded,
+                ToPlayer::StateMutation(Mutation::DamagePlayer(p, 3)) if p == PlayerId::new(2)
+            ));
+
+            let message = FromPlayer::Hello {
+                version: 1,
+                features: vec![Feature::MessagePack],
+            };
+            let frame = Frame::encode(&message, encoding).unwrap();
+            let decoded: FromPlayer = frame.decode().unwrap();
+            assert!(matches!(
+                decoded,
+                FromPlayer::Hello { version: 1, features } if features == [Feature::MessagePack]
+            ));
+        }
+    }
+}